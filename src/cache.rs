@@ -2,7 +2,7 @@ use chrono::{DateTime, Local};
 use tokio::fs::{read_to_string, write};
 use crate::models::DataItem;
 
-/// Writes history data to file
+/// Writes history data to file, unless the day is not over yet since its history is still growing
 ///
 /// # Arguments
 ///
@@ -11,6 +11,10 @@ use crate::models::DataItem;
 /// * 'date_time' - date to use as name for the file to create
 /// * 'data' - data to store
 pub async fn store_cache_data(cache_dir: &str, prefix: &str, date_time: DateTime<Local>, data: &Vec<DataItem>) -> Result<(), std::io::Error> {
+    if !is_complete(date_time) {
+        return Ok(());
+    }
+
     let name = date_time.format("%Y-%m-%d").to_string();
     let path = format!("{}{}-{}.json", cache_dir, prefix, name);
 
@@ -21,7 +25,8 @@ pub async fn store_cache_data(cache_dir: &str, prefix: &str, date_time: DateTime
 }
 
 
-/// Tries to read history data from file
+/// Tries to read history data from file, there is never any cached data for a day that is not
+/// over yet
 ///
 /// # Arguments
 ///
@@ -29,6 +34,10 @@ pub async fn store_cache_data(cache_dir: &str, prefix: &str, date_time: DateTime
 /// * 'prefix' - prefix to identify source
 /// * 'date_time' - date to use as name for the file to read
 pub async fn read_cache_data(cache_dir: &str, prefix: &str, date_time: DateTime<Local>) -> Result<Option<Vec<DataItem>>, std::io::Error> {
+    if !is_complete(date_time) {
+        return Ok(None);
+    }
+
     let name = date_time.format("%Y-%m-%d").to_string();
     let path = format!("{}{}-{}.json", cache_dir, prefix, name);
 
@@ -38,4 +47,13 @@ pub async fn read_cache_data(cache_dir: &str, prefix: &str, date_time: DateTime<
     } else {
         Ok(None)
     }
-}
\ No newline at end of file
+}
+
+/// Returns true if the day is over, i.e. its history is final
+///
+/// # Arguments
+///
+/// * 'date_time' - point in time within the day
+fn is_complete(date_time: DateTime<Local>) -> bool {
+    date_time.date_naive() < Local::now().date_naive()
+}
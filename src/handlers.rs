@@ -1,15 +1,37 @@
-use chrono::{Local, TimeZone};
-use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeDelta, TimeZone};
+use actix_web::{get, post, web, HttpResponse, Responder};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use tokio::fs::{read_to_string, write};
 use crate::AppState;
 use crate::initialization::Config;
+use crate::manager_analysis::backtest::{backtest, clear_sky_models, load_forecasts, save_forecast, Backtest, ForecastRecord, HorizonSkill};
+use crate::manager_analysis::clouds::infer_clouds;
+use crate::manager_analysis::nowcast::{nowcast, Nowcast};
+use crate::manager_analysis::horizon::{learn_horizon, HorizonFit};
+use crate::manager_analysis::sensitivity::{sensitivity, OatEffect, Sensitivity};
+use crate::manager_analysis::sweep::{sweep, SweepPoint};
+use crate::manager_energy::battery::{simulate_battery, BatterySpec, EnergySummary};
+use crate::manager_energy::economics::{monthly_value, value_day, GridFlows, Tariff, ValueDay, ValueMonth, ValueSummary};
+use crate::manager_energy::curtailment::{actual_curtailment, modelled_curtailment, monthly_curtailment, CurtailmentDay, CurtailmentMonth};
+use crate::manager_energy::load::LoadProfile;
+use crate::manager_energy::prices::SpotPrices;
+use crate::manager_energy::surplus::{surplus, surplus_windows, SurplusWindow};
 use crate::manager_fox_cloud::Fox;
-use crate::manager_production::get_day_production;
+use crate::manager_production::{get_day_production, get_range_production, get_variant_production};
+use crate::manager_production::benchmark::solar_benchmark;
+use crate::manager_production::pipeline::{ModelChoice, SolarPositionChoice};
+use crate::manager_weather::source::WeatherVariable;
 use crate::manager_weather::Weather;
-use crate::models::{DataItem, Parameters};
+use crate::metrics::{fit_metrics, FitMetrics};
+use crate::models::{DataItem, Moisture, Parameters, Production};
+use crate::serialize_timestamp;
 
-#[derive(Deserialize, Serialize)]
+/// Maximum number of days in one range request, each day is fetched from the weather source and
+/// the inverter one after another
+const MAX_DAYS: u32 = 366;
+
+#[derive(Deserialize, Serialize, Clone)]
 struct Params {
     pub year: i32,
     pub month: u32,
@@ -28,29 +50,308 @@ struct Params {
     pub stop_elv: f64,
 }
 
+#[derive(Serialize)]
+struct Series {
+    pub name: String,
+    #[serde(rename(serialize = "type"))]
+    pub chart_type: String,
+    pub data: Vec<DataItem>,
+}
+
+/// One model variant in a comparison, parameters not given are taken from the base parameters
+///
+#[derive(Deserialize)]
+struct Variant {
+    pub name: String,
+    #[serde(default)]
+    pub params: Map<String, Value>,
+    pub models: Option<ModelChoice>,
+}
+
+#[derive(Deserialize)]
+struct Comparison {
+    pub params: Params,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Deserialize)]
+struct Range {
+    pub params: Params,
+    pub days: u32,
+}
+
+#[derive(Deserialize)]
+struct Benchmark {
+    pub year: i32,
+    pub step: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SensitivityRequest {
+    pub params: Params,
+    pub scale: Option<f64>,
+    pub trajectories: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct CategoryItem {
+    pub x: String,
+    pub y: f64,
+}
+
+#[derive(Serialize)]
+struct CategorySeries {
+    pub name: String,
+    pub data: Vec<CategoryItem>,
+}
+
+#[derive(Deserialize)]
+struct SweepRequest {
+    pub params: Params,
+    pub parameter: String,
+    pub from: f64,
+    pub to: f64,
+    pub step: f64,
+    pub days: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct HorizonRequest {
+    pub params: Params,
+    pub days: Option<u32>,
+    pub bin_width: Option<f64>,
+    #[serde(default)]
+    pub save: bool,
+}
+
+#[derive(Deserialize)]
+struct CloudsRequest {
+    pub params: Params,
+    pub window: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct NowcastRequest {
+    pub params: Params,
+    pub now: Option<usize>,
+    pub lookback: Option<usize>,
+    pub decay: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct BatteryRequest {
+    pub params: Params,
+    pub days: Option<u32>,
+    pub battery: BatterySpec,
+    pub load: Option<LoadProfile>,
+}
+
+#[derive(Deserialize)]
+struct CurtailmentRequest {
+    pub params: Params,
+    pub days: Option<u32>,
+    pub export_limit: f64,
+    pub load: Option<LoadProfile>,
+    #[serde(default)]
+    pub actual: bool,
+}
+
+#[derive(Deserialize)]
+struct EconomicsRequest {
+    pub params: Params,
+    pub days: Option<u32>,
+    pub load: Option<LoadProfile>,
+    pub tariff: Option<Tariff>,
+    pub battery: Option<BatterySpec>,
+    #[serde(default)]
+    pub variants: Vec<Variant>,
+    #[serde(default)]
+    pub actual: bool,
+}
+
+#[derive(Deserialize)]
+struct SurplusRequest {
+    pub params: Params,
+    pub threshold: f64,
+    pub min_minutes: usize,
+    pub load: Option<LoadProfile>,
+    #[serde(default)]
+    pub forecast: bool,
+    pub now: Option<usize>,
+    pub lookback: Option<usize>,
+    pub decay: Option<f64>,
+}
+
+/// Weather for one day as used by the production model
+///
+#[derive(Clone, Copy)]
+struct DayWeather {
+    pub temp: [f64;1440],
+    pub temp_previous: Option<[f64;1440]>,
+    pub humidity: Option<[f64;1440]>,
+    pub cloud_cover: Option<[f64;1440]>,
+}
+
+#[derive(Serialize)]
+struct ValueItem {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Serialize)]
+struct ValueSeries {
+    pub name: String,
+    pub data: Vec<ValueItem>,
+}
+
 #[get("/get_data")]
 pub async fn get_data(data: web::Data<AppState>, params: web::Query<Params>) -> impl Responder {
-    let json = get_web_data(&data.config, &params).await;
-    save_parameters(&data.config.files.cache_dir, &params).await;
-
-    HttpResponse::Ok().body(json)
+    match get_web_data(&data.config, &params).await {
+        Ok(json) => {
+            save_parameters(&data.config.files.cache_dir, &params).await;
+            HttpResponse::Ok().body(json)
+        },
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
 }
 
 #[get("/get_start")]
 pub async fn get_start(data: web::Data<AppState>) -> impl Responder {
-    let params = load_parameters(&data.config.files.cache_dir).await;
-    let json = get_web_data(&data.config, &params).await;
+    let result = match load_parameters(&data.config.files.cache_dir).await {
+        Ok(params) => get_web_data(&data.config, &params).await,
+        Err(e) => Err(e),
+    };
 
-    HttpResponse::Ok().body(json)
+    match result {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+#[post("/get_comparison")]
+pub async fn get_comparison(data: web::Data<AppState>, comparison: web::Json<Comparison>) -> impl Responder {
+    match get_comparison_data(&data.config, &comparison).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+#[post("/get_range")]
+pub async fn get_range(data: web::Data<AppState>, range: web::Json<Range>) -> impl Responder {
+    match get_range_data(&data.config, &range).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+/// Benchmarks the solar position models over a full year using the saved parameters and a
+/// constant ambient temperature
+///
+#[get("/get_solar_benchmark")]
+pub async fn get_solar_benchmark(data: web::Data<AppState>, benchmark: web::Query<Benchmark>) -> impl Responder {
+    match get_solar_benchmark_data(&data.config, &benchmark).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+#[post("/get_sensitivity")]
+pub async fn get_sensitivity(data: web::Data<AppState>, request: web::Json<SensitivityRequest>) -> impl Responder {
+    match get_sensitivity_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+#[post("/get_sweep")]
+pub async fn get_sweep(data: web::Data<AppState>, request: web::Json<SweepRequest>) -> impl Responder {
+    match get_sweep_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+/// Learns a horizon mask from the clear days in a range, optionally saving it for the horizon
+/// shading model
+///
+#[post("/get_horizon")]
+pub async fn get_horizon(data: web::Data<AppState>, request: web::Json<HorizonRequest>) -> impl Responder {
+    match get_horizon_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+/// Infers clouds from the clear-sky index of the day and re-runs the model with them as cloud
+/// cover, returning the index diagram and the re-run temperatures
+///
+#[post("/get_clouds")]
+pub async fn get_clouds(data: web::Data<AppState>, request: web::Json<CloudsRequest>) -> impl Responder {
+    match get_clouds_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
 }
 
-async fn load_parameters(cache_dir: &str) -> Params {
+/// Corrects the rest of the day's estimate by how actual production has compared with the model
+/// over the last hour(s)
+///
+#[post("/get_nowcast")]
+pub async fn get_nowcast(data: web::Data<AppState>, request: web::Json<NowcastRequest>) -> impl Responder {
+    match get_nowcast_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+/// Compares the forecasts stored for a range of days with actual production
+///
+#[post("/get_backtest")]
+pub async fn get_backtest(data: web::Data<AppState>, range: web::Json<Range>) -> impl Responder {
+    match get_backtest_data(&data.config, &range).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+#[post("/get_battery")]
+pub async fn get_battery(data: web::Data<AppState>, request: web::Json<BatteryRequest>) -> impl Responder {
+    match get_battery_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+#[post("/get_curtailment")]
+pub async fn get_curtailment(data: web::Data<AppState>, request: web::Json<CurtailmentRequest>) -> impl Responder {
+    match get_curtailment_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+#[post("/get_economics")]
+pub async fn get_economics(data: web::Data<AppState>, request: web::Json<EconomicsRequest>) -> impl Responder {
+    match get_economics_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+#[post("/get_surplus")]
+pub async fn get_surplus(data: web::Data<AppState>, request: web::Json<SurplusRequest>) -> impl Responder {
+    match get_surplus_data(&data.config, request.into_inner()).await {
+        Ok(json) => HttpResponse::Ok().body(json),
+        Err(e) => HttpResponse::BadRequest().body(e),
+    }
+}
+
+async fn load_parameters(cache_dir: &str) -> Result<Params, String> {
     let path = format!("{}parameters.json", cache_dir);
 
-    let json = read_to_string(path).await.unwrap();
-    let params: Params = serde_json::from_str(&json).unwrap();
+    let json = read_to_string(&path).await.map_err(|e| format!("unable to read {}: {}", path, e))?;
 
-    params
+    serde_json::from_str(&json).map_err(|e| format!("invalid {}: {}", path, e))
 }
 
 async fn save_parameters(cache_dir: &str, params: &Params) {
@@ -60,55 +361,19 @@ async fn save_parameters(cache_dir: &str, params: &Params) {
     write(path, json).await.unwrap();
 }
 
-async fn get_web_data(config: &Config, params: &Params) -> String {
-    let date_time = Local::now()
-        .timezone()
-        .with_ymd_and_hms(params.year, params.month, params.day, 0, 0, 0)
-        .unwrap();
+async fn get_web_data(config: &Config, params: &Params) -> Result<String, String> {
+    let (weather, history) = get_day_inputs(config, params).await?;
+    let production_params = production_parameters(config, params, &weather);
 
-    let temp = Weather::new(&config.weather.host, &config.weather.sensor)
-        .unwrap()
-        .get_temp_history(date_time, &config.files.cache_dir).await.unwrap();
+    let mut estimated = get_day_production(production_params, &config.model, &config.files.cache_dir).map_err(|e| e.to_string())?;
+    let temp_diagram = temp_diagram(&mut estimated);
 
-    let history = Fox::new(&config.fox_ess)
-        .unwrap()
-        .get_device_history_data(date_time, &config.files.cache_dir).await.unwrap();
-
-    let production_params = Parameters {
-        year: params.year,
-        month: params.month,
-        day: params.day,
-        lat: config.geo_ref.lat,
-        long: config.geo_ref.long,
-        temp,
-        panel_power: params.panel_power,
-        panel_slope: params.panel_slope,
-        panel_east_azm: params.panel_east_azm,
-        panel_temp_red: params.panel_temp_red,
-        tau: params.tau,
-        tau_down: params.tau_down,
-        k_gain: params.k_gain,
-        iam_factor: params.iam_factor,
-        start_azm: params.start_azm,
-        start_elv: params.start_elv,
-        stop_azm: params.stop_azm,
-        stop_elv: params.stop_elv,   
-    };
-
-    let estimated = get_day_production(production_params).unwrap();
-
-    #[derive(Serialize)]
-    pub struct Series {
-        pub name: String,
-        #[serde(rename(serialize = "type"))]
-        pub chart_type: String,
-        pub data: Vec<DataItem>,
-    }
     #[derive(Serialize)]
     struct WebData<'a> {
         prod_diagram: (Series, Series),
-        incidence_diagram: (Series, Series),
-        temp_diagram: (Series, Series, Series),
+        incidence_diagram: (Series, Series, Series),
+        temp_diagram: Vec<Series>,
+        moisture: Vec<Moisture>,
         ame_diagram: Series,
         params: &'a Params,
     }
@@ -131,20 +396,13 @@ async fn get_web_data(config: &Config, params: &Params) -> String {
             name: "West".to_string(),
             chart_type: "line".to_string(),
             data: estimated.incidence_west,
-        }),
-        temp_diagram: (Series {
-            name: "Ambient".to_string(),
-            chart_type: "line".to_string(),
-            data: estimated.ambient_temperature,
-        }, Series {
-            name: "East".to_string(),
-            chart_type: "line".to_string(),
-            data: estimated.roof_temperature_east,
         }, Series {
-            name: "West".to_string(),
+            name: "Slope".to_string(),
             chart_type: "line".to_string(),
-            data: estimated.roof_temperature_west,
+            data: estimated.surface_slope,
         }),
+        temp_diagram,
+        moisture: estimated.moisture,
         ame_diagram: Series {
             name: "Air mass effect".to_string(),
             chart_type: "line".to_string(),
@@ -154,7 +412,965 @@ async fn get_web_data(config: &Config, params: &Params) -> String {
 
     };
 
-    let json = serde_json::to_string(&web_data).unwrap();
+    Ok(serde_json::to_string(&web_data).unwrap())
+}
+/// Returns production estimates for each variant in a comparison together with their fit
+/// against the actual production, all for the date given in the base parameters
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'comparison' - base parameters and variants to compare
+async fn get_comparison_data(config: &Config, comparison: &Comparison) -> Result<String, String> {
+    let (weather, history) = get_day_inputs(config, &comparison.params).await?;
+    let base = serde_json::to_value(&comparison.params).unwrap();
+
+    #[derive(Serialize)]
+    struct VariantFit<'a> {
+        name: &'a str,
+        params: Params,
+        models: ModelChoice,
+        metrics: FitMetrics,
+    }
+    #[derive(Serialize)]
+    struct ComparisonData<'a> {
+        prod_diagram: Vec<Series>,
+        variants: Vec<VariantFit<'a>>,
+    }
+
+    let runs = comparison.variants.iter()
+        .map(|variant| {
+            let params = variant_params(&base, variant)?;
+            let models = variant.models.unwrap_or(config.model);
+            Ok((params, models))
+        })
+        .collect::<Result<Vec<(Params, ModelChoice)>, String>>()?;
+
+    let production_params = runs.iter()
+        .map(|(params, models)| (production_parameters(config, params, &weather), *models))
+        .collect::<Vec<(Parameters, ModelChoice)>>();
+    let cache_dir = config.files.cache_dir.clone();
+    let estimates = web::block(move || get_variant_production(&production_params, &cache_dir))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut prod_diagram: Vec<Series> = Vec::new();
+    let mut variants: Vec<VariantFit> = Vec::new();
+
+    for ((variant, (params, models)), estimated) in comparison.variants.iter().zip(runs).zip(estimates) {
+        let estimated = estimated.map_err(|e| format!("variant {}: {}", variant.name, e))?;
+
+        variants.push(VariantFit {
+            name: &variant.name,
+            params,
+            models,
+            metrics: fit_metrics(&history, &estimated.power),
+        });
+        prod_diagram.push(Series {
+            name: variant.name.clone(),
+            chart_type: "line".to_string(),
+            data: estimated.power,
+        });
+    }
+
+    prod_diagram.insert(0, Series {
+        name: "Actual".to_string(),
+        chart_type: "area".to_string(),
+        data: history,
+    });
+
+    Ok(serde_json::to_string(&ComparisonData { prod_diagram, variants }).unwrap())
+}
+
+/// Returns daily estimated and actual energy, with fit metrics per day, for a range of days
+/// starting at the date in the parameters
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'range' - parameters and number of days to simulate
+async fn get_range_data(config: &Config, range: &Range) -> Result<String, String> {
+    let (production_params, histories) = get_range_inputs(config, &range.params, range.days).await?;
+
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+    let estimated = web::block(move || get_range_production(&production_params, &models, &cache_dir))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    #[derive(Serialize)]
+    struct DayFit {
+        #[serde(with = "serialize_timestamp")]
+        date: DateTime<Local>,
+        metrics: FitMetrics,
+    }
+    #[derive(Serialize)]
+    struct RangeData {
+        energy_diagram: (Series, Series),
+        days: Vec<DayFit>,
+    }
+
+    let days = estimated.iter()
+        .zip(histories.iter())
+        .map(|(e, h)| DayFit { date: e.power[0].x, metrics: fit_metrics(h, &e.power) })
+        .collect::<Vec<DayFit>>();
+
+    let energy_diagram = (Series {
+        name: "Actual".to_string(),
+        chart_type: "column".to_string(),
+        data: days.iter().map(|d| DataItem { x: d.date, y: d.metrics.actual_energy }).collect(),
+    }, Series {
+        name: "Estimated".to_string(),
+        chart_type: "column".to_string(),
+        data: days.iter().map(|d| DataItem { x: d.date, y: d.metrics.estimated_energy }).collect(),
+    });
+
+    Ok(serde_json::to_string(&RangeData { energy_diagram, days }).unwrap())
+}
+
+/// Returns production parameters and actual production for each day in a range starting at
+/// the parameters date
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'params' - parameters for the first day
+/// * 'days' - number of days
+async fn get_range_inputs(config: &Config, params: &Params, days: u32) -> Result<(Vec<Parameters>, Vec<Vec<DataItem>>), String> {
+    let mut histories: Vec<Vec<DataItem>> = Vec::new();
+    let mut production_params: Vec<Parameters> = Vec::new();
+
+    for params in day_params(params, days)? {
+        let (weather, history) = get_day_inputs(config, &params).await?;
+        production_params.push(production_parameters(config, &params, &weather));
+        histories.push(history);
+    }
+
+    Ok((production_params, histories))
+}
+
+/// Returns a copy of the parameters for each day in a range starting at the parameters date,
+/// an error for ranges longer than `MAX_DAYS`
+///
+/// # Arguments
+///
+/// * 'params' - parameters for the first day
+/// * 'days' - number of days
+fn day_params(params: &Params, days: u32) -> Result<Vec<Params>, String> {
+    if days > MAX_DAYS {
+        return Err(format!("too many days in range, max is {}", MAX_DAYS));
+    }
+    let first = NaiveDate::from_ymd_opt(params.year, params.month, params.day)
+        .ok_or(format!("invalid date {}-{}-{}", params.year, params.month, params.day))?;
+
+    Ok(first.iter_days()
+        .take(days as usize)
+        .map(|d| Params { year: d.year(), month: d.month(), day: d.day(), ..params.clone() })
+        .collect())
+}
+
+/// Returns timing and deviation of the solar position models over a full year
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'benchmark' - year to run and step of the interpolated SPA model
+async fn get_solar_benchmark_data(config: &Config, benchmark: &Benchmark) -> Result<String, String> {
+    let params = load_parameters(&config.files.cache_dir).await?;
+    let first = Params { year: benchmark.year, month: 1, day: 1, ..params };
+    let last = NaiveDate::from_ymd_opt(benchmark.year, 12, 31).ok_or(format!("invalid year {}", benchmark.year))?;
+    let days = if last.leap_year() { 366 } else { 365 };
+
+    let production_params = day_params(&first, days)?
+        .iter()
+        .map(|p| production_parameters(config, p, &DayWeather { temp: [15.0; 1440], temp_previous: None, humidity: None, cloud_cover: None }))
+        .collect::<Vec<Parameters>>();
+
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+    let choices = [
+        SolarPositionChoice::SpaInterpolated { step: benchmark.step.unwrap_or(10) },
+        SolarPositionChoice::Psa,
+    ];
+
+    let bench = web::block(move || solar_benchmark(&production_params, &models, &cache_dir, &choices))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    Ok(serde_json::to_string(&bench).unwrap())
+}
+
+/// Returns the sensitivity of energy and fit error to each tunable parameter for the date in
+/// the parameters, together with a tornado diagram of the one-at-a-time energy changes
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters to analyse around and analysis settings
+async fn get_sensitivity_data(config: &Config, request: SensitivityRequest) -> Result<String, String> {
+    let (weather, history) = get_day_inputs(config, &request.params).await?;
+    let production_params = production_parameters(config, &request.params, &weather);
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+    let scale = request.scale.unwrap_or(1.0);
+    let trajectories = request.trajectories.unwrap_or(10);
+
+    let result = web::block(move || sensitivity(&production_params, &history, &models, &cache_dir, scale, trajectories))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let mut oat = result.oat.iter().collect::<Vec<_>>();
+    oat.sort_by(|a, b| {
+        let range = |e: &OatEffect| (e.high.energy - e.low.energy).abs();
+        range(b).total_cmp(&range(a))
+    });
+
+    let tornado_diagram = ["Low", "High"].map(|side| CategorySeries {
+        name: side.to_string(),
+        data: oat.iter()
+            .map(|e| {
+                let outcome = if side == "Low" { e.low } else { e.high };
+                CategoryItem { x: e.name.to_string(), y: outcome.energy - result.base.energy }
+            })
+            .collect(),
+    });
+
+    #[derive(Serialize)]
+    struct SensitivityData {
+        tornado_diagram: [CategorySeries; 2],
+        sensitivity: Sensitivity,
+    }
+
+    Ok(serde_json::to_string(&SensitivityData { tornado_diagram, sensitivity: result }).unwrap())
+}
+
+/// Returns fit error and energy for each value of one parameter over one or more days
+/// starting at the date in the parameters
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters, parameter to sweep, its range and number of days
+async fn get_sweep_data(config: &Config, request: SweepRequest) -> Result<String, String> {
+    let (production_params, histories) = get_range_inputs(config, &request.params, request.days.unwrap_or(1)).await?;
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+    let (from, to, step) = (request.from, request.to, request.step);
+    let name = request.parameter.clone();
+
+    let points = web::block(move || sweep(&production_params, &histories, &name, from, to, step, &models, &cache_dir))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let series = |name: &str, f: fn(&SweepPoint) -> f64| ValueSeries {
+        name: name.to_string(),
+        data: points.iter().map(|p| ValueItem { x: p.value, y: f(p) }).collect(),
+    };
+
+    #[derive(Serialize)]
+    struct SweepData {
+        parameter: String,
+        best: Option<f64>,
+        sweep_diagram: [ValueSeries; 3],
+        points: Vec<SweepPoint>,
+    }
 
-    json
-}
\ No newline at end of file
+    let sweep_data = SweepData {
+        parameter: request.parameter,
+        best: points.iter()
+            .min_by(|a, b| a.metrics.rmse.total_cmp(&b.metrics.rmse))
+            .map(|p| p.value),
+        sweep_diagram: [
+            series("RMSE", |p| p.metrics.rmse),
+            series("Estimated", |p| p.metrics.estimated_energy),
+            series("Actual", |p| p.metrics.actual_energy),
+        ],
+        points,
+    };
+
+    Ok(serde_json::to_string(&sweep_data).unwrap())
+}
+
+/// Returns a horizon mask learned from the clear days in a range together with a diagram of it
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters for the first day, number of days, bin width and whether to save
+async fn get_horizon_data(config: &Config, request: HorizonRequest) -> Result<String, String> {
+    let (production_params, histories) = get_range_inputs(config, &request.params, request.days.unwrap_or(30)).await?;
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+    let bin_width = request.bin_width.unwrap_or(5.0);
+
+    let fit = web::block(move || learn_horizon(&production_params, &histories, &models, &cache_dir, bin_width))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    if request.save {
+        fit.mask.save(&config.files.cache_dir).map_err(|e| e.to_string())?;
+    }
+
+    let series = |name: &str, values: &[f64]| ValueSeries {
+        name: name.to_string(),
+        data: values.iter()
+            .enumerate()
+            .map(|(i, v)| ValueItem { x: (i as f64 + 0.5) * fit.mask.bin_width, y: *v })
+            .collect(),
+    };
+
+    #[derive(Serialize)]
+    struct HorizonData<'a> {
+        saved: bool,
+        horizon_diagram: [ValueSeries; 2],
+        fit: &'a HorizonFit,
+    }
+
+    let horizon_data = HorizonData {
+        saved: request.save,
+        horizon_diagram: [
+            series("Horizon", &fit.mask.elevation),
+            series("Transmittance", &fit.mask.transmittance),
+        ],
+        fit: &fit,
+    };
+
+    Ok(serde_json::to_string(&horizon_data).unwrap())
+}
+
+/// Returns the weather, with the previous day's ambient temperature if available, and the actual
+/// production for the date in the parameters, an error if the weather source has no temperature
+/// for the date
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'params' - parameters from the web client
+async fn get_day_inputs(config: &Config, params: &Params) -> Result<(DayWeather, Vec<DataItem>), String> {
+    let date_time = Local::now()
+        .timezone()
+        .with_ymd_and_hms(params.year, params.month, params.day, 0, 0, 0)
+        .single()
+        .ok_or(format!("invalid date {}-{}-{}", params.year, params.month, params.day))?;
+
+    let weather = Weather::new(&config.weather, &config.geo_ref).map_err(|e| e.to_string())?;
+    let bundle = weather.get_history(date_time, &config.files.cache_dir).await.map_err(|e| e.to_string())?;
+    let temp_previous = weather.get_temp_history(date_time - TimeDelta::days(1), &config.files.cache_dir).await.ok();
+    let day_weather = DayWeather {
+        temp: bundle.get(WeatherVariable::Temperature)
+            .ok_or(format!("no temperature from the weather source for {}", date_time.format("%Y-%m-%d")))?,
+        temp_previous,
+        humidity: bundle.get(WeatherVariable::Humidity),
+        cloud_cover: bundle.get(WeatherVariable::CloudCover),
+    };
+
+    let history = Fox::new(&config.fox_ess)
+        .map_err(|e| e.to_string())?
+        .get_device_history_data(date_time, &config.files.cache_dir).await
+        .map_err(|e| e.to_string())?;
+
+    Ok((day_weather, history))
+}
+
+/// Returns the clear-sky index of a day, the clouds inferred from it and the temperatures from a
+/// re-run of the model with the inferred cloud cover and beam attenuation
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters and smoothing window in minutes
+async fn get_clouds_data(config: &Config, request: CloudsRequest) -> Result<String, String> {
+    let (weather, history) = get_day_inputs(config, &request.params).await?;
+    let production_params = production_parameters(config, &request.params, &weather);
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+    let window = request.window.unwrap_or(30);
+
+    let (inference, mut rerun) = web::block(move || {
+        let estimated = get_day_production(production_params.clone(), &models, &cache_dir).map_err(|e| e.to_string())?;
+        let inference = infer_clouds(&estimated, &history, window).map_err(|e| e.to_string())?;
+
+        let cloudy_params = Parameters {
+            cloud_cover: Some(inference.cloud_cover_per_minute),
+            clouds: Some(inference.clouds_per_minute),
+            ..production_params
+        };
+        let rerun = get_day_production(cloudy_params, &models, &cache_dir).map_err(|e| e.to_string())?;
+
+        Ok::<_, String>((inference, rerun))
+    })
+        .await
+        .map_err(|e| e.to_string())??;
+
+    #[derive(Serialize)]
+    struct CloudsData {
+        cloud_diagram: Vec<Series>,
+        temp_diagram: Vec<Series>,
+        moisture: Vec<Moisture>,
+    }
+
+    let series = |name: &str, chart_type: &str, data: Vec<DataItem>| Series {
+        name: name.to_string(),
+        chart_type: chart_type.to_string(),
+        data,
+    };
+
+    let clouds_data = CloudsData {
+        cloud_diagram: vec![
+            series("Clear-sky index", "scatter", inference.index),
+            series("Smoothed", "line", inference.smoothed),
+            series("Attenuation", "line", inference.attenuation),
+            series("Cloud cover", "line", inference.cloud_cover),
+        ],
+        temp_diagram: temp_diagram(&mut rerun),
+        moisture: std::mem::take(&mut rerun.moisture),
+    };
+
+    Ok(serde_json::to_string(&clouds_data).unwrap())
+}
+
+/// Returns a nowcast for the rest of the day in the parameters together with a production diagram
+/// of actual power, the model estimate and the nowcast
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters, minute of day to nowcast from, lookback and decay in minutes
+async fn get_nowcast_data(config: &Config, request: NowcastRequest) -> Result<String, String> {
+    let (weather, history) = get_day_inputs(config, &request.params).await?;
+    let production_params = production_parameters(config, &request.params, &weather);
+    let (estimated, clear_sky) = forecast_production(config, production_params).await?;
+
+    let mut result = nowcast(&estimated, &history, request.now, request.lookback.unwrap_or(60), request.decay.unwrap_or(180.0))
+        .map_err(|e| e.to_string())?;
+    store_forecast(config, ForecastRecord::from_nowcast(&result, &estimated, &clear_sky)).await?;
+
+    #[derive(Serialize)]
+    struct NowcastData {
+        prod_diagram: Vec<Series>,
+        nowcast: Nowcast,
+    }
+
+    let nowcast_data = NowcastData {
+        prod_diagram: vec![Series {
+            name: "Actual".to_string(),
+            chart_type: "area".to_string(),
+            data: history,
+        }, Series {
+            name: "Estimated".to_string(),
+            chart_type: "line".to_string(),
+            data: estimated.power,
+        }, Series {
+            name: "Nowcast".to_string(),
+            chart_type: "line".to_string(),
+            data: std::mem::take(&mut result.remainder),
+        }],
+        nowcast: result,
+    };
+
+    Ok(serde_json::to_string(&nowcast_data).unwrap())
+}
+
+/// Returns the stored forecasts for a range of days compared with actual production, together
+/// with a diagram of skill scores per hour of horizon
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'range' - parameters for the first day and number of days
+async fn get_backtest_data(config: &Config, range: &Range) -> Result<String, String> {
+    let fox = Fox::new(&config.fox_ess).map_err(|e| e.to_string())?;
+    let mut days: Vec<(Vec<ForecastRecord>, Vec<DataItem>)> = Vec::new();
+
+    for params in day_params(&range.params, range.days)? {
+        let date_time = Local::now()
+            .timezone()
+            .with_ymd_and_hms(params.year, params.month, params.day, 0, 0, 0)
+            .single()
+            .ok_or(format!("invalid date {}-{}-{}", params.year, params.month, params.day))?;
+
+        let cache_dir = config.files.cache_dir.clone();
+        let records = web::block(move || load_forecasts(&cache_dir, date_time.date_naive()))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+        if records.is_empty() {
+            continue;
+        }
+        let actual = fox.get_device_history_data(date_time, &config.files.cache_dir).await.map_err(|e| e.to_string())?;
+        days.push((records, actual));
+    }
+    if days.is_empty() {
+        return Err("no stored forecasts in the range".to_string());
+    }
+
+    let result = backtest(&days);
+    let series = |name: &str, f: fn(&HorizonSkill) -> f64| ValueSeries {
+        name: name.to_string(),
+        data: result.skill.iter().map(|s| ValueItem { x: s.horizon as f64, y: f(s) }).collect(),
+    };
+
+    #[derive(Serialize)]
+    struct BacktestData<'a> {
+        skill_diagram: [ValueSeries; 2],
+        backtest: &'a Backtest,
+    }
+
+    let backtest_data = BacktestData {
+        skill_diagram: [
+            series("vs Persistence", |s| s.skill_persistence),
+            series("vs Clear sky", |s| s.skill_clear_sky),
+        ],
+        backtest: &result,
+    };
+
+    Ok(serde_json::to_string(&backtest_data).unwrap())
+}
+
+/// Returns a home battery simulated on top of the production estimate and a household load
+/// profile for a range of days, with power and state of charge diagrams and energy summaries
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters for the first day, number of days, battery and load profile
+async fn get_battery_data(config: &Config, request: BatteryRequest) -> Result<String, String> {
+    let load = request.load.unwrap_or_default().per_minute().map_err(|e| e.to_string())?;
+    let (production_params, _) = get_range_inputs(config, &request.params, request.days.unwrap_or(1)).await?;
+
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+    let estimated = web::block(move || get_range_production(&production_params, &models, &cache_dir))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let inputs = estimated.iter()
+        .map(|e| (e.power.as_slice(), load))
+        .collect::<Vec<(&[DataItem], [f64;1440])>>();
+    let mut result = simulate_battery(&request.battery, &inputs).map_err(|e| e.to_string())?;
+
+    #[derive(Serialize)]
+    struct DaySummary {
+        #[serde(with = "serialize_timestamp")]
+        date: DateTime<Local>,
+        summary: EnergySummary,
+    }
+    #[derive(Serialize)]
+    struct BatteryData {
+        power_diagram: Vec<Series>,
+        soc_diagram: Vec<Series>,
+        days: Vec<DaySummary>,
+        total: EnergySummary,
+    }
+
+    let series = |name: &str, data: Vec<DataItem>| Series {
+        name: name.to_string(),
+        chart_type: "line".to_string(),
+        data,
+    };
+    let mut production: Vec<DataItem> = Vec::new();
+    let mut load_data: Vec<DataItem> = Vec::new();
+    let (mut import, mut export, mut battery, mut soc, mut self_consumption) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for (e, day) in estimated.iter().zip(result.iter_mut()) {
+        production.extend(e.power.iter().map(|d| DataItem { x: d.x, y: d.y }));
+        load_data.extend(e.power.iter().zip(load.iter()).map(|(d, l)| DataItem { x: d.x, y: *l }));
+        import.append(&mut day.grid_import);
+        export.append(&mut day.grid_export);
+        battery.append(&mut day.battery);
+        soc.append(&mut day.soc);
+        self_consumption.append(&mut day.self_consumption);
+    }
+
+    let days = estimated.iter()
+        .zip(result.iter())
+        .map(|(e, d)| DaySummary { date: e.power[0].x, summary: d.summary })
+        .collect::<Vec<DaySummary>>();
+
+    let battery_data = BatteryData {
+        power_diagram: vec![
+            series("Production", production),
+            series("Load", load_data),
+            series("Import", import),
+            series("Export", export),
+            series("Battery", battery),
+            series("Self-consumption", self_consumption),
+        ],
+        soc_diagram: vec![series("SoC", soc)],
+        total: EnergySummary::total(&days.iter().map(|d| d.summary).collect::<Vec<EnergySummary>>()),
+        days,
+    };
+
+    Ok(serde_json::to_string(&battery_data).unwrap())
+}
+
+/// Returns the parameters of a variant, those not given taken from the base parameters and the
+/// date always from the base
+///
+/// # Arguments
+///
+/// * 'base' - base parameters as a JSON value
+/// * 'variant' - the variant
+fn variant_params(base: &Value, variant: &Variant) -> Result<Params, String> {
+    let mut merged = base.clone();
+    if let Value::Object(m) = &mut merged {
+        variant.params.iter()
+            .filter(|(k, _)| !["year", "month", "day"].contains(&k.as_str()))
+            .for_each(|(k, v)| { m.insert(k.clone(), v.clone()); });
+    }
+
+    serde_json::from_value(merged).map_err(|e| format!("variant {}: {}", variant.name, e))
+}
+
+/// Returns the production curtailed by an export limit for a range of modelled days and, if asked
+/// for, the same days as recorded by the inverter, summarised per day and month
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters for the first day, number of days, export limit and load profile
+async fn get_curtailment_data(config: &Config, request: CurtailmentRequest) -> Result<String, String> {
+    if request.export_limit < 0.0 {
+        return Err("export limit must not be negative".to_string());
+    }
+    let load = request.load.unwrap_or_default().per_minute().map_err(|e| e.to_string())?;
+    let days = request.days.unwrap_or(1);
+    let (production_params, _) = get_range_inputs(config, &request.params, days).await?;
+
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+    let estimated = web::block(move || get_range_production(&production_params, &models, &cache_dir))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let modelled = estimated.iter()
+        .map(|e| modelled_curtailment(&e.power, &load, request.export_limit))
+        .collect::<Result<Vec<CurtailmentDay>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let mut actual: Vec<CurtailmentDay> = Vec::new();
+    if request.actual {
+        let fox = Fox::new(&config.fox_ess).map_err(|e| e.to_string())?;
+        let cache_dir = &config.files.cache_dir;
+        for params in day_params(&request.params, days)? {
+            let date_time = Local::now()
+                .timezone()
+                .with_ymd_and_hms(params.year, params.month, params.day, 0, 0, 0)
+                .single()
+                .ok_or(format!("invalid date {}-{}-{}", params.year, params.month, params.day))?;
+
+            let production = fox.get_device_history_data(date_time, cache_dir).await.map_err(|e| e.to_string())?;
+            let feed_in = fox.get_feed_in_history_data(date_time, cache_dir).await.map_err(|e| e.to_string())?;
+            let load = fox.get_load_history_data(date_time, cache_dir).await.map_err(|e| e.to_string())?;
+            actual.push(actual_curtailment(&production, &feed_in, &load, request.export_limit).map_err(|e| e.to_string())?);
+        }
+    }
+
+    #[derive(Serialize)]
+    struct CurtailmentSet {
+        days: Vec<CurtailmentDay>,
+        months: Vec<CurtailmentMonth>,
+    }
+    #[derive(Serialize)]
+    struct CurtailmentData {
+        energy_diagram: Vec<Series>,
+        power_diagram: Vec<Series>,
+        modelled: CurtailmentSet,
+        actual: CurtailmentSet,
+    }
+
+    let mut energy_diagram: Vec<Series> = Vec::new();
+    let mut power_diagram: Vec<Series> = Vec::new();
+    for (name, days) in [("Modelled", &modelled), ("Actual", &actual)] {
+        if days.is_empty() {
+            continue;
+        }
+        energy_diagram.push(Series {
+            name: name.to_string(),
+            chart_type: "column".to_string(),
+            data: days.iter().map(|d| DataItem { x: d.date, y: d.summary.curtailed }).collect(),
+        });
+        power_diagram.push(Series {
+            name: name.to_string(),
+            chart_type: "line".to_string(),
+            data: days.iter().flat_map(|d| d.curtailed.iter().map(|c| DataItem { x: c.x, y: c.y })).collect(),
+        });
+    }
+
+    let curtailment_data = CurtailmentData {
+        energy_diagram,
+        power_diagram,
+        modelled: CurtailmentSet { months: monthly_curtailment(&modelled), days: modelled },
+        actual: CurtailmentSet { months: monthly_curtailment(&actual), days: actual },
+    };
+
+    Ok(serde_json::to_string(&curtailment_data).unwrap())
+}
+
+/// Returns what production is worth at spot prices for a range of days, per day and month, for
+/// the estimate and optionally with a battery, for other layouts and as recorded by the inverter
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters for the first day, number of days, load, tariff and what to value
+async fn get_economics_data(config: &Config, request: EconomicsRequest) -> Result<String, String> {
+    let cache_dir = &config.files.cache_dir;
+    let prices = SpotPrices::new(cache_dir).map_err(|e| e.to_string())?;
+    let tariff = match request.tariff {
+        Some(tariff) => tariff,
+        None => Tariff::new(cache_dir).map_err(|e| e.to_string())?,
+    };
+    let load = request.load.unwrap_or_default().per_minute().map_err(|e| e.to_string())?;
+    let days = request.days.unwrap_or(1);
+    let (production_params, _) = get_range_inputs(config, &request.params, days).await?;
+
+    // Production parameters and models per layout, the estimate first
+    let base = serde_json::to_value(&request.params).unwrap();
+    let mut layouts: Vec<(String, Vec<Parameters>, ModelChoice)> = vec![("Estimated".to_string(), production_params.clone(), config.model)];
+    for variant in request.variants.iter() {
+        let params = variant_params(&base, variant)?;
+        let variant_params = production_params.iter()
+            .map(|p| {
+                let weather = DayWeather { temp: p.temp, temp_previous: p.temp_previous, humidity: p.humidity, cloud_cover: p.cloud_cover };
+                Parameters { year: p.year, month: p.month, day: p.day, ..production_parameters(config, &params, &weather) }
+            })
+            .collect::<Vec<Parameters>>();
+        layouts.push((variant.name.clone(), variant_params, variant.models.unwrap_or(config.model)));
+    }
+
+    let value_days = |flows: Vec<GridFlows>| flows.iter()
+        .map(|f| value_day(f, &prices, &tariff))
+        .collect::<Result<Vec<ValueDay>, _>>()
+        .map_err(|e| e.to_string());
+
+    let mut valuations: Vec<(String, Vec<ValueDay>)> = Vec::new();
+    for (name, params, models) in layouts {
+        let cache_dir = cache_dir.clone();
+        let estimated = web::block(move || get_range_production(&params, &models, &cache_dir))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("{}: {}", name, e))?;
+
+        valuations.push((name.clone(), value_days(estimated.iter().map(|e| GridFlows::direct(&e.power, &load)).collect())?));
+
+        if let (Some(battery), true) = (&request.battery, valuations.len() == 1) {
+            let inputs = estimated.iter()
+                .map(|e| (e.power.as_slice(), load))
+                .collect::<Vec<(&[DataItem], [f64;1440])>>();
+            let battery_days = simulate_battery(battery, &inputs).map_err(|e| e.to_string())?;
+            let flows = estimated.iter()
+                .zip(battery_days.iter())
+                .map(|(e, b)| GridFlows::with_battery(&e.power, &load, b))
+                .collect();
+            valuations.push((format!("{} + battery", name), value_days(flows)?));
+        }
+    }
+
+    if request.actual {
+        let fox = Fox::new(&config.fox_ess).map_err(|e| e.to_string())?;
+        let mut flows: Vec<GridFlows> = Vec::new();
+        for params in day_params(&request.params, days)? {
+            let date_time = Local::now()
+                .timezone()
+                .with_ymd_and_hms(params.year, params.month, params.day, 0, 0, 0)
+                .single()
+                .ok_or(format!("invalid date {}-{}-{}", params.year, params.month, params.day))?;
+
+            let production = fox.get_device_history_data(date_time, cache_dir).await.map_err(|e| e.to_string())?;
+            let feed_in = fox.get_feed_in_history_data(date_time, cache_dir).await.map_err(|e| e.to_string())?;
+            let load = fox.get_load_history_data(date_time, cache_dir).await.map_err(|e| e.to_string())?;
+            flows.push(GridFlows::recorded(&production, &feed_in, &load).map_err(|e| e.to_string())?);
+        }
+        valuations.push(("Actual".to_string(), value_days(flows)?));
+    }
+
+    #[derive(Serialize)]
+    struct Valuation {
+        name: String,
+        days: Vec<ValueDay>,
+        months: Vec<ValueMonth>,
+        total: ValueSummary,
+    }
+    #[derive(Serialize)]
+    struct EconomicsData {
+        value_diagram: Vec<Series>,
+        tariff: Tariff,
+        valuations: Vec<Valuation>,
+    }
+
+    let value_diagram = valuations.iter()
+        .map(|(name, days)| Series {
+            name: name.clone(),
+            chart_type: "column".to_string(),
+            data: days.iter().map(|d| DataItem { x: d.date, y: d.summary.value }).collect(),
+        })
+        .collect();
+
+    let valuations = valuations.into_iter()
+        .map(|(name, days)| {
+            let months = monthly_value(&days);
+            let total = months.iter().fold(ValueSummary::default(), |mut t, m| { t.add(&m.summary); t });
+            Valuation { name, days, months, total }
+        })
+        .collect();
+
+    Ok(serde_json::to_string(&EconomicsData { value_diagram, tariff, valuations }).unwrap())
+}
+
+/// Returns the windows of the day where production exceeds the load by a threshold for long
+/// enough to run a shiftable load, from the production estimate or, in forecast mode, from the
+/// nowcast for the rest of the day
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'request' - parameters, threshold, minimum duration, load profile and forecast settings
+async fn get_surplus_data(config: &Config, request: SurplusRequest) -> Result<String, String> {
+    let load = request.load.unwrap_or_default().per_minute().map_err(|e| e.to_string())?;
+    let (weather, history) = get_day_inputs(config, &request.params).await?;
+    let production_params = production_parameters(config, &request.params, &weather);
+
+    let (name, power) = if request.forecast {
+        let (estimated, clear_sky) = forecast_production(config, production_params).await?;
+        let result = nowcast(&estimated, &history, request.now, request.lookback.unwrap_or(60), request.decay.unwrap_or(180.0))
+            .map_err(|e| e.to_string())?;
+        store_forecast(config, ForecastRecord::from_nowcast(&result, &estimated, &clear_sky)).await?;
+        ("Nowcast", result.remainder)
+    } else {
+        let models = config.model;
+        let cache_dir = config.files.cache_dir.clone();
+        let estimated = web::block(move || get_day_production(production_params, &models, &cache_dir))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+        ("Estimated", estimated.power)
+    };
+
+    let surplus = surplus(&power, &load);
+    let windows = surplus_windows(&surplus, request.threshold, request.min_minutes).map_err(|e| e.to_string())?;
+
+    #[derive(Serialize)]
+    struct SurplusData {
+        surplus_diagram: Vec<Series>,
+        windows: Vec<SurplusWindow>,
+    }
+
+    let load_data = power.iter()
+        .zip(surplus.iter())
+        .map(|(p, s)| DataItem { x: p.x, y: p.y - s.y })
+        .collect();
+    let series = |name: &str, data: Vec<DataItem>| Series {
+        name: name.to_string(),
+        chart_type: "line".to_string(),
+        data,
+    };
+
+    let surplus_data = SurplusData {
+        surplus_diagram: vec![series(name, power), series("Load", load_data), series("Surplus", surplus)],
+        windows,
+    };
+
+    Ok(serde_json::to_string(&surplus_data).unwrap())
+}
+
+/// Returns the production estimate a forecast corrects together with the unshaded clear-sky
+/// estimate of the same day, the baseline the forecast is later scored against
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'params' - parameters for the production model
+async fn forecast_production(config: &Config, params: Parameters) -> Result<(Production, Production), String> {
+    let models = config.model;
+    let cache_dir = config.files.cache_dir.clone();
+
+    let mut estimates = web::block(move || get_variant_production(&[(params.clone(), models), (params, clear_sky_models(&models))], &cache_dir))
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter();
+
+    match (estimates.next(), estimates.next()) {
+        (Some(estimated), Some(clear_sky)) => Ok((estimated.map_err(|e| e.to_string())?, clear_sky.map_err(|e| e.to_string())?)),
+        _ => Err("no production for the day".to_string()),
+    }
+}
+
+/// Stores a forecast for later backtesting on a blocking thread
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'record' - the forecast
+async fn store_forecast(config: &Config, record: ForecastRecord) -> Result<(), String> {
+    let cache_dir = config.files.cache_dir.clone();
+
+    web::block(move || save_forecast(&cache_dir, record))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the temperature diagram series, taking them out of the production
+///
+/// # Arguments
+///
+/// * 'production' - production to take the temperature series from
+fn temp_diagram(production: &mut Production) -> Vec<Series> {
+    [
+        ("Ambient", &mut production.ambient_temperature),
+        ("Module East", &mut production.module_temperature_east),
+        ("Module West", &mut production.module_temperature_west),
+        ("Roof East", &mut production.roof_temperature_east),
+        ("Roof West", &mut production.roof_temperature_west),
+        ("Sky", &mut production.sky_temperature),
+        ("Dew point", &mut production.dew_point),
+    ]
+        .into_iter()
+        .map(|(name, data)| Series {
+            name: name.to_string(),
+            chart_type: "line".to_string(),
+            data: std::mem::take(data),
+        })
+        .collect()
+}
+
+/// Returns parameters for the production model
+///
+/// # Arguments
+///
+/// * 'config' - configuration struct
+/// * 'params' - parameters from the web client
+/// * 'weather' - weather for the day
+fn production_parameters(config: &Config, params: &Params, weather: &DayWeather) -> Parameters {
+    Parameters {
+        year: params.year,
+        month: params.month,
+        day: params.day,
+        lat: config.geo_ref.lat,
+        long: config.geo_ref.long,
+        temp: weather.temp,
+        temp_previous: weather.temp_previous,
+        humidity: weather.humidity,
+        cloud_cover: weather.cloud_cover,
+        clouds: None,
+        panel_power: params.panel_power,
+        panel_slope: params.panel_slope,
+        panel_east_azm: params.panel_east_azm,
+        panel_temp_red: params.panel_temp_red,
+        tau: params.tau,
+        tau_down: params.tau_down,
+        k_gain: params.k_gain,
+        iam_factor: params.iam_factor,
+        start_azm: params.start_azm,
+        start_elv: params.start_elv,
+        stop_azm: params.stop_azm,
+        stop_elv: params.stop_elv,
+    }
+}
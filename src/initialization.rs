@@ -3,6 +3,8 @@ use log::LevelFilter;
 use serde::Deserialize;
 use crate::errors::ConfigError;
 use crate::logging::setup_logger;
+use crate::manager_production::pipeline::ModelChoice;
+use crate::manager_weather::source::WeatherSourceChoice;
 
 #[derive(Deserialize, Clone)]
 pub struct GeoRef {
@@ -18,8 +20,15 @@ pub struct FoxESS {
 
 #[derive(Deserialize, Clone)]
 pub struct Weather {
+    #[serde(default)]
+    pub source: WeatherSourceChoice,
+    #[serde(default)]
     pub host: String,
+    #[serde(default)]
     pub sensor: String,
+    pub path: Option<String>,
+    pub url: Option<String>,
+    pub station: Option<String>,
 }
 
 #[derive(Deserialize, Clone)]
@@ -48,6 +57,8 @@ pub struct Config {
     pub files: Files,
     pub web_server: WebServerParameters,
     pub general: General,
+    #[serde(default)]
+    pub model: ModelChoice,
 }
 
 /// Returns a configuration struct for the application and starts logging
@@ -62,7 +73,7 @@ pub fn config() -> Result<Config, ConfigError> {
         .ok_or(ConfigError::from("invalid --config=<config_path>"))?
         .1;
 
-    let config = load_config(&config_path)?;
+    let config = load_config(config_path)?;
 
     setup_logger(&config.general.log_path, config.general.log_level, config.general.log_to_stdout)?;
 
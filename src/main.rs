@@ -8,12 +8,16 @@ mod models;
 mod manager_weather;
 mod cache;
 mod serialize_timestamp;
+mod metrics;
+mod parallel;
+mod manager_analysis;
+mod manager_energy;
 
 use actix_web::{middleware, web, App, HttpServer};
 use actix_files::Files;
 use log::info;
 use crate::errors::UnrecoverableError;
-use crate::handlers::{get_data, get_start};
+use crate::handlers::{get_backtest, get_battery, get_clouds, get_comparison, get_curtailment, get_data, get_economics, get_horizon, get_nowcast, get_range, get_sensitivity, get_solar_benchmark, get_start, get_surplus, get_sweep};
 use crate::initialization::{config, Config};
 
 struct AppState {
@@ -31,6 +35,19 @@ async fn main() -> Result<(), UnrecoverableError> {
             .app_data(web_data.clone())
             .service(get_data)
             .service(get_start)
+            .service(get_comparison)
+            .service(get_range)
+            .service(get_solar_benchmark)
+            .service(get_sensitivity)
+            .service(get_sweep)
+            .service(get_horizon)
+            .service(get_clouds)
+            .service(get_nowcast)
+            .service(get_backtest)
+            .service(get_battery)
+            .service(get_curtailment)
+            .service(get_economics)
+            .service(get_surplus)
             .service(
                 web::scope("")
                     .wrap(middleware::DefaultHeaders::new().add(("Cache-Control", "no-cache")))
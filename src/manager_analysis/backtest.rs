@@ -0,0 +1,239 @@
+use std::fs;
+use std::sync::Mutex;
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use crate::manager_analysis::errors::AnalysisError;
+use crate::manager_analysis::nowcast::Nowcast;
+use crate::manager_production::pipeline::{ModelChoice, SelfShadingChoice, ShadingChoice};
+use crate::metrics::{actual_energy, estimated_energy};
+use crate::models::{DataItem, Production};
+use crate::serialize_timestamp;
+
+/// Prefix of the files in the cache directory holding the forecasts issued for a day
+const FORECAST_PREFIX: &str = "forecast";
+
+/// Held while forecast files are read or written so concurrent requests do not lose records
+static FORECAST_FILES: Mutex<()> = Mutex::new(());
+
+/// A forecast as issued, kept to be compared with actual production once it is known
+///
+#[derive(Serialize, Deserialize)]
+pub struct ForecastRecord {
+    /// Point in time the forecast was issued for, its first minute
+    #[serde(with = "serialize_timestamp")]
+    pub issued: DateTime<Local>,
+    /// Actual over estimated power at issue, used for the persistence baseline
+    pub ratio: f64,
+    /// Forecast power per minute from issue to the end of the day
+    pub forecast: Vec<DataItem>,
+    /// Model estimate the forecast corrects for the same minutes, shading included
+    #[serde(default)]
+    pub estimate: Vec<DataItem>,
+    /// Unshaded clear-sky model estimate for the same minutes, the clear-sky baseline
+    pub clear_sky: Vec<DataItem>,
+}
+
+impl ForecastRecord {
+    /// Returns a record of a nowcast
+    ///
+    /// # Arguments
+    ///
+    /// * 'nowcast' - the nowcast with its remainder
+    /// * 'estimated' - the production estimate the nowcast corrects
+    /// * 'clear_sky' - the production estimate of the same day without shading, see `clear_sky_models`
+    pub fn from_nowcast(nowcast: &Nowcast, estimated: &Production, clear_sky: &Production) -> Self {
+        let remainder = |p: &Production| p.power[p.power.len() - nowcast.remainder.len()..].iter()
+            .map(|d| DataItem { x: d.x, y: d.y })
+            .collect::<Vec<DataItem>>();
+
+        Self {
+            issued: nowcast.now,
+            ratio: nowcast.ratio,
+            forecast: nowcast.remainder.iter().map(|d| DataItem { x: d.x, y: d.y }).collect(),
+            estimate: remainder(estimated),
+            clear_sky: remainder(clear_sky),
+        }
+    }
+}
+
+/// Returns the model variant for the clear-sky baseline, the given models without any shading
+///
+/// # Arguments
+///
+/// * 'models' - model variant used for forecasts
+pub fn clear_sky_models(models: &ModelChoice) -> ModelChoice {
+    ModelChoice { shading: ShadingChoice::None, self_shading: SelfShadingChoice::None, ..*models }
+}
+
+/// Fit per hour of forecast horizon, with skill scores `1 - rmse / rmse of baseline`
+///
+#[derive(Serialize)]
+pub struct HorizonSkill {
+    /// Hours ahead of issue, this bin covers from `horizon` to `horizon + 1` hours
+    pub horizon: usize,
+    pub samples: usize,
+    /// Root mean square errors [kW]
+    pub rmse_forecast: f64,
+    pub rmse_persistence: f64,
+    pub rmse_clear_sky: f64,
+    pub skill_persistence: f64,
+    pub skill_clear_sky: f64,
+}
+
+/// Summary of one stored forecast against what actually happened
+///
+#[derive(Serialize)]
+pub struct ForecastSummary {
+    #[serde(with = "serialize_timestamp")]
+    pub issued: DateTime<Local>,
+    /// Minutes covered by the forecast
+    pub minutes: usize,
+    /// Forecast energy from issue to the end of the day [kWh]
+    pub forecast_energy: f64,
+    /// Actual energy from issue to the end of the day [kWh]
+    pub actual_energy: f64,
+}
+
+#[derive(Serialize)]
+pub struct Backtest {
+    pub forecasts: Vec<ForecastSummary>,
+    pub skill: Vec<HorizonSkill>,
+}
+
+/// Stores a forecast together with earlier forecasts for the same day, replacing any issued at
+/// the same point in time
+///
+/// The file is locked from reading the earlier forecasts until the new ones are written.
+///
+/// # Arguments
+///
+/// * 'cache_dir' - directory to store forecasts in
+/// * 'record' - the forecast
+pub fn save_forecast(cache_dir: &str, record: ForecastRecord) -> Result<(), AnalysisError> {
+    let _files = FORECAST_FILES.lock().unwrap();
+    let date = record.issued.date_naive();
+    let mut records = read_forecasts(cache_dir, date)?;
+    records.retain(|r| r.issued != record.issued);
+    records.push(record);
+    records.sort_by_key(|r| r.issued);
+
+    let path = forecast_path(cache_dir, date);
+    let json = serde_json::to_string(&records).map_err(|e| e.to_string())?;
+
+    fs::write(&path, json).map_err(|e| format!("unable to write {}: {}", path, e).into())
+}
+
+/// Returns the forecasts stored for a day, none if there are none
+///
+/// # Arguments
+///
+/// * 'cache_dir' - directory holding forecasts
+/// * 'date' - the day
+pub fn load_forecasts(cache_dir: &str, date: NaiveDate) -> Result<Vec<ForecastRecord>, AnalysisError> {
+    let _files = FORECAST_FILES.lock().unwrap();
+
+    read_forecasts(cache_dir, date)
+}
+
+/// Returns the forecasts stored for a day without locking the forecast files
+///
+/// # Arguments
+///
+/// * 'cache_dir' - directory holding forecasts
+/// * 'date' - the day
+fn read_forecasts(cache_dir: &str, date: NaiveDate) -> Result<Vec<ForecastRecord>, AnalysisError> {
+    let path = forecast_path(cache_dir, date);
+
+    match fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("invalid {}: {}", path, e).into()),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// Returns stored forecasts compared with actual production, with skill scores per hour of
+/// horizon against the persistence and the clear-sky baselines
+///
+/// The persistence baseline keeps the ratio between actual and estimated power at issue for the
+/// rest of the day, the clear-sky baseline is the model estimate without shading. Only minutes
+/// where the clear-sky estimate has any power are compared, records stored without the estimate
+/// count in the summaries but not in the skill scores.
+///
+/// # Arguments
+///
+/// * 'days' - forecasts and actual power, one pair per day
+pub fn backtest(days: &[(Vec<ForecastRecord>, Vec<DataItem>)]) -> Backtest {
+    let mut forecasts: Vec<ForecastSummary> = Vec::new();
+    // Sums of squared errors per hour of horizon, forecast, persistence and clear sky
+    let mut bins: Vec<(usize, f64, f64, f64)> = Vec::new();
+
+    for (records, actual) in days {
+        for record in records {
+            let Some(start) = record.forecast.first().map(|d| d.x) else {
+                continue;
+            };
+            let covered = actual.iter()
+                .filter(|a| a.x >= start)
+                .map(|a| DataItem { x: a.x, y: a.y })
+                .collect::<Vec<DataItem>>();
+            let minutes = if record.estimate.len() == record.forecast.len() && record.clear_sky.len() == record.forecast.len() {
+                record.forecast.len()
+            } else {
+                0
+            };
+
+            for a in covered.iter() {
+                let minute = (a.x - start).num_minutes() as usize;
+                if minute >= minutes || record.clear_sky[minute].y <= 0.0 {
+                    continue;
+                }
+
+                let hour = minute / 60;
+                if bins.len() <= hour {
+                    bins.resize(hour + 1, (0, 0.0, 0.0, 0.0));
+                }
+                let bin = &mut bins[hour];
+                bin.0 += 1;
+                bin.1 += (record.forecast[minute].y - a.y).powi(2);
+                bin.2 += (record.ratio * record.estimate[minute].y - a.y).powi(2);
+                bin.3 += (record.clear_sky[minute].y - a.y).powi(2);
+            }
+
+            forecasts.push(ForecastSummary {
+                issued: record.issued,
+                minutes: record.forecast.len(),
+                forecast_energy: estimated_energy(&record.forecast),
+                actual_energy: actual_energy(&covered),
+            });
+        }
+    }
+
+    let skill_score = |rmse: f64, baseline: f64| if baseline > 0.0 { 1.0 - rmse / baseline } else { 0.0 };
+    let skill = bins.into_iter()
+        .enumerate()
+        .filter(|(_, (n, ..))| *n > 0)
+        .map(|(horizon, (n, forecast, persistence, clear_sky))| {
+            let rmse = |sse: f64| (sse / n as f64).sqrt();
+            HorizonSkill {
+                horizon,
+                samples: n,
+                rmse_forecast: rmse(forecast),
+                rmse_persistence: rmse(persistence),
+                rmse_clear_sky: rmse(clear_sky),
+                skill_persistence: skill_score(rmse(forecast), rmse(persistence)),
+                skill_clear_sky: skill_score(rmse(forecast), rmse(clear_sky)),
+            }
+        })
+        .collect();
+
+    Backtest { forecasts, skill }
+}
+
+/// Returns the path of the forecast file for a day
+///
+/// # Arguments
+///
+/// * 'cache_dir' - directory holding forecasts
+/// * 'date' - the day
+fn forecast_path(cache_dir: &str, date: NaiveDate) -> String {
+    format!("{}{}-{}.json", cache_dir, FORECAST_PREFIX, date.format("%Y-%m-%d"))
+}
@@ -0,0 +1,116 @@
+use serde::Serialize;
+use crate::manager_analysis::errors::AnalysisError;
+use crate::models::{DataItem, Production};
+
+/// Share of the estimate's peak above which minutes get a clear-sky index, below that the index
+/// is dominated by noise and diffuse light
+const INDEX_SHARE: f64 = 0.05;
+
+/// Highest clear-sky index kept, cloud edges can lift actual power above clear sky for a while
+const MAX_INDEX: f64 = 1.5;
+
+/// Clear-sky index and inferred clouds for one day
+///
+/// The clear-sky index is actual power over the clear-sky estimate. Smoothed over a window it
+/// gives the cloud attenuation of the beam, which is turned into a cloud cover by inverting the
+/// Kasten & Czeplak relation `k = 1 - 0.75 N^3.4`.
+///
+#[derive(Serialize)]
+pub struct CloudInference {
+    pub index: Vec<DataItem>,
+    pub smoothed: Vec<DataItem>,
+    pub attenuation: Vec<DataItem>,
+    pub cloud_cover: Vec<DataItem>,
+    /// Cloud cover per minute of the day, interpolated between samples and held before the first
+    /// and after the last, to feed back into the production model
+    #[serde(skip)]
+    pub cloud_cover_per_minute: [f64;1440],
+    /// Share of the beam let through the clouds per minute of the day, one minus the attenuation,
+    /// interpolated as the cloud cover to feed the thermal model's `clouds` input
+    #[serde(skip)]
+    pub clouds_per_minute: [f64;1440],
+}
+
+/// Returns the clear-sky index of a day together with the clouds inferred from it
+///
+/// # Arguments
+///
+/// * 'estimated' - clear-sky production estimate for the day
+/// * 'actual' - actual power for the day
+/// * 'window' - width of the centred smoothing window in minutes
+pub fn infer_clouds(estimated: &Production, actual: &[DataItem], window: usize) -> Result<CloudInference, AnalysisError> {
+    let start = estimated.power.first().ok_or("no estimate for the day")?.x;
+    let peak = estimated.power.iter().map(|d| d.y).fold(0.0, f64::max);
+
+    // Clear-sky index per actual sample, as minute of day and index
+    let samples = actual.iter()
+        .filter_map(|a| {
+            let minute = (a.x - start).num_minutes();
+            if minute < 0 || minute as usize >= estimated.power.len() {
+                return None;
+            }
+            let est = estimated.power[minute as usize].y;
+            (est > INDEX_SHARE * peak).then(|| (minute as usize, (a.y / est).clamp(0.0, MAX_INDEX)))
+        })
+        .collect::<Vec<(usize, f64)>>();
+    if samples.is_empty() {
+        return Err("no daylight actual power to infer clouds from".into());
+    }
+
+    let half = (window / 2) as i64;
+    let smoothed = samples.iter()
+        .map(|(m, _)| {
+            let near = samples.iter()
+                .filter(|(n, _)| (*n as i64 - *m as i64).abs() <= half)
+                .map(|(_, k)| k)
+                .collect::<Vec<&f64>>();
+            (*m, near.iter().copied().sum::<f64>() / near.len() as f64)
+        })
+        .collect::<Vec<(usize, f64)>>();
+
+    let cover = smoothed.iter()
+        .map(|(m, k)| (*m, cloud_cover(*k)))
+        .collect::<Vec<(usize, f64)>>();
+
+    let series = |values: &[(usize, f64)], f: &dyn Fn(f64) -> f64| values.iter()
+        .map(|(m, v)| DataItem { x: estimated.power[*m].x, y: f(*v) })
+        .collect::<Vec<DataItem>>();
+
+    Ok(CloudInference {
+        index: series(&samples, &|k| k),
+        smoothed: series(&smoothed, &|k| k),
+        attenuation: series(&smoothed, &|k| (1.0 - k).clamp(0.0, 1.0)),
+        cloud_cover: series(&cover, &|n| n),
+        cloud_cover_per_minute: per_minute(&cover),
+        clouds_per_minute: per_minute(&smoothed.iter().map(|(m, k)| (*m, k.clamp(0.0, 1.0))).collect::<Vec<(usize, f64)>>()),
+    })
+}
+
+/// Returns the cloud cover [0,1] that attenuates the beam to the given clear-sky index
+///
+/// # Arguments
+///
+/// * 'index' - clear-sky index
+fn cloud_cover(index: f64) -> f64 {
+    ((1.0 - index).clamp(0.0, 0.75) / 0.75).powf(1.0 / 3.4)
+}
+
+/// Returns values per minute of the day linearly interpolated between samples ordered by minute
+///
+/// # Arguments
+///
+/// * 'samples' - minute of day and value, at least one
+fn per_minute(samples: &[(usize, f64)]) -> [f64;1440] {
+    let mut result: [f64;1440] = [samples[0].1; 1440];
+
+    for pair in samples.windows(2) {
+        let ((m0, v0), (m1, v1)) = (pair[0], pair[1]);
+        for (m, r) in result.iter_mut().enumerate().take(m1 + 1).skip(m0) {
+            *r = v0 + (v1 - v0) * (m - m0) as f64 / (m1 - m0).max(1) as f64;
+        }
+    }
+    let (last_minute, last) = samples[samples.len() - 1];
+    result.iter_mut().skip(last_minute).for_each(|r| *r = last);
+
+    result
+}
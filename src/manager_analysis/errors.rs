@@ -0,0 +1,20 @@
+use std::fmt;
+use std::fmt::Formatter;
+use crate::manager_production::errors::ProdError;
+
+#[derive(Debug)]
+pub struct AnalysisError(pub String);
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "AnalysisError: {}", self.0)
+    }
+}
+impl From<&str> for AnalysisError {
+    fn from(e: &str) -> Self { AnalysisError(e.to_string()) }
+}
+impl From<String> for AnalysisError {
+    fn from(e: String) -> Self { AnalysisError(e) }
+}
+impl From<ProdError> for AnalysisError {
+    fn from(e: ProdError) -> Self { AnalysisError(e.to_string()) }
+}
@@ -0,0 +1,187 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use crate::manager_analysis::errors::AnalysisError;
+use crate::manager_production::get_range_production;
+use crate::manager_production::horizon::HorizonMask;
+use crate::manager_production::pipeline::{ModelChoice, SelfShadingChoice, ShadingChoice};
+use crate::models::{DataItem, Parameters, Production};
+
+/// Share of the unshaded estimate's peak above which minutes are used to scale and judge a day
+const CORE_SHARE: f64 = 0.5;
+
+/// Share of the unshaded estimate's peak above which minutes are used to learn the horizon
+const SAMPLE_SHARE: f64 = 0.05;
+
+/// Largest scaled residual, relative to the peak, for a day to count as clear
+const CLEAR_RESIDUAL: f64 = 0.05;
+
+/// Largest mean ratio below the horizon for it to count as shading
+const SHADED_RATIO: f64 = 0.9;
+
+/// Fewest samples below the horizon in an azimuth bin
+const MIN_BIN_SAMPLES: usize = 3;
+
+/// Horizon mask learned from clear days together with what it was learned from
+///
+#[derive(Serialize)]
+pub struct HorizonFit {
+    pub mask: HorizonMask,
+    pub clear_days: Vec<NaiveDate>,
+    /// Number of minutes used
+    pub samples: usize,
+}
+
+/// Sun position and actual to unshaded power ratio for one minute of a clear day
+///
+struct Sample {
+    azimuth: f64,
+    elevation: f64,
+    ratio: f64,
+}
+
+/// Returns a horizon mask learned from the days among the given that are clear
+///
+/// Each day is compared with the unshaded model. A day is clear when actual power follows the
+/// scaled unshaded estimate closely around noon, where shading is least likely. For clear days
+/// every minute's ratio of actual to scaled unshaded power is binned by sun azimuth, and per bin
+/// the horizon is put at the elevation where the ratio steps down. The transmittance includes the
+/// diffuse light still reaching shaded panels.
+///
+/// # Arguments
+///
+/// * 'days' - parameters, one per day
+/// * 'actual' - actual power, one series per day
+/// * 'models' - model variant to use for each stage of the production pipeline, shading is ignored
+/// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+/// * 'bin_width' - azimuth bin width in degrees
+pub fn learn_horizon(days: &[Parameters], actual: &[Vec<DataItem>], models: &ModelChoice, cache_dir: &str, bin_width: f64) -> Result<HorizonFit, AnalysisError> {
+    if days.is_empty() || days.len() != actual.len() {
+        return Err("one actual series per day is required".into());
+    }
+    if bin_width <= 0.0 || bin_width > 90.0 {
+        return Err("bin width must be within (0,90] degrees".into());
+    }
+
+    let unshaded = ModelChoice { shading: ShadingChoice::None, self_shading: SelfShadingChoice::None, ..*models };
+    let estimated = get_range_production(days, &unshaded, cache_dir)?;
+
+    let mut clear_days: Vec<NaiveDate> = Vec::new();
+    let mut samples: Vec<Sample> = Vec::new();
+
+    for ((params, production), actual) in days.iter().zip(&estimated).zip(actual) {
+        if let Some(mut day_samples) = clear_day_samples(production, actual) {
+            clear_days.push(NaiveDate::from_ymd_opt(params.year, params.month, params.day).unwrap());
+            samples.append(&mut day_samples);
+        }
+    }
+    if clear_days.is_empty() {
+        return Err("no clear days found in the range".into());
+    }
+
+    let bins = (360.0 / bin_width).ceil() as usize;
+    let mut binned: Vec<Vec<&Sample>> = (0..bins).map(|_| Vec::new()).collect();
+    for s in samples.iter() {
+        binned[((s.azimuth.rem_euclid(360.0) / bin_width) as usize).min(bins - 1)].push(s);
+    }
+
+    let (elevation, transmittance) = binned.iter_mut()
+        .map(|bin| horizon_in_bin(bin))
+        .unzip();
+
+    Ok(HorizonFit {
+        mask: HorizonMask { bin_width, elevation, transmittance },
+        clear_days,
+        samples: samples.len(),
+    })
+}
+
+/// Returns the samples of a day if it is clear, otherwise none
+///
+/// # Arguments
+///
+/// * 'production' - unshaded estimate for the day
+/// * 'actual' - actual power for the day
+fn clear_day_samples(production: &Production, actual: &[DataItem]) -> Option<Vec<Sample>> {
+    let start = production.power.first()?.x;
+    let peak = production.power.iter().map(|d| d.y).fold(0.0, f64::max);
+    if peak <= 0.0 {
+        return None;
+    }
+
+    // Actual power matched to the estimate at the same minute
+    let pairs = actual.iter()
+        .filter_map(|a| {
+            let minute = (a.x - start).num_minutes();
+            (minute >= 0 && (minute as usize) < production.power.len()).then(|| (minute as usize, a.y, production.power[minute as usize].y))
+        })
+        .collect::<Vec<(usize, f64, f64)>>();
+
+    let core = pairs.iter().filter(|(_, _, e)| *e > CORE_SHARE * peak).collect::<Vec<_>>();
+    if core.len() < 10 {
+        return None;
+    }
+
+    let scale = core.iter().map(|(_, a, e)| a * e).sum::<f64>() / core.iter().map(|(_, _, e)| e * e).sum::<f64>();
+    let residual = (core.iter().map(|(_, a, e)| (a - scale * e).powi(2)).sum::<f64>() / core.len() as f64).sqrt() / peak;
+    if residual > CLEAR_RESIDUAL || !(0.5..=1.5).contains(&scale) {
+        return None;
+    }
+
+    Some(pairs.iter()
+        .filter(|(_, _, e)| *e > SAMPLE_SHARE * peak)
+        .map(|(m, a, e)| Sample {
+            azimuth: production.sun_azimuth[*m].y,
+            elevation: production.sun_elevation[*m].y,
+            ratio: (a / (scale * e)).clamp(0.0, 1.5),
+        })
+        .collect())
+}
+
+/// Returns the horizon elevation and transmittance for one azimuth bin
+///
+/// The ratios in the bin, ordered by elevation, are fitted by least squares with a step that is
+/// at the transmittance below the horizon and at 1 above it, since each day is already scaled to
+/// its unshaded minutes. When every sample in the bin is shaded the horizon is put at the highest
+/// of them, the sun has not been seen above it in that direction.
+///
+/// # Arguments
+///
+/// * 'bin' - samples in the bin
+fn horizon_in_bin(bin: &mut [&Sample]) -> (f64, f64) {
+    bin.sort_by(|a, b| a.elevation.total_cmp(&b.elevation));
+    let n = bin.len();
+
+    // Sum of squared errors for split i, samples 0..i below the horizon, from running sums
+    let mut above_sq = bin.iter().map(|s| (s.ratio - 1.0).powi(2)).sum::<f64>();
+    let (mut below, mut below_sq) = (0.0, 0.0);
+    let mut best: Option<(usize, f64)> = None;
+    for i in 1..=n {
+        let r = bin[i - 1].ratio;
+        below += r;
+        below_sq += r * r;
+        above_sq -= (r - 1.0).powi(2);
+        if i < MIN_BIN_SAMPLES {
+            continue;
+        }
+
+        let sse = below_sq - below * below / i as f64 + above_sq;
+        if best.is_none_or(|(_, b)| sse < b) {
+            best = Some((i, sse));
+        }
+    }
+
+    let Some((split, _)) = best else {
+        return (0.0, 1.0);
+    };
+    let transmittance = bin[..split].iter().map(|s| s.ratio).sum::<f64>() / split as f64;
+    if transmittance > SHADED_RATIO {
+        return (0.0, 1.0);
+    }
+
+    let elevation = match bin.get(split) {
+        Some(above) => (bin[split - 1].elevation + above.elevation) / 2.0,
+        None => bin[split - 1].elevation,
+    };
+
+    (elevation, transmittance.max(0.0))
+}
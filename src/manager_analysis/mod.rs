@@ -0,0 +1,103 @@
+pub mod backtest;
+pub mod clouds;
+pub mod errors;
+pub mod horizon;
+pub mod nowcast;
+pub mod sensitivity;
+pub mod sweep;
+
+use crate::models::Parameters;
+
+/// Tunable model parameters together with a typical perturbation step in the parameter's own unit
+pub const TUNABLE: [(&str, f64); 12] = [
+    ("panel_power", 20.0),
+    ("panel_slope", 2.0),
+    ("panel_east_azm", 5.0),
+    ("panel_temp_red", 0.1),
+    ("tau", 0.1),
+    ("tau_down", 0.1),
+    ("k_gain", 5.0),
+    ("iam_factor", 0.5),
+    ("start_azm", 5.0),
+    ("start_elv", 2.0),
+    ("stop_azm", 5.0),
+    ("stop_elv", 2.0),
+];
+
+/// Shortest thermal time constant [h], the one minute time step of the thermal model
+const MIN_TIME_CONSTANT: f64 = 1.0 / 60.0;
+
+/// Returns the smallest and largest value of a tunable parameter the production model accepts
+///
+/// # Arguments
+///
+/// * 'name' - name of the parameter field
+pub fn valid_range(name: &str) -> (f64, f64) {
+    match name {
+        "panel_slope" | "start_elv" | "stop_elv" => (0.0, 90.0),
+        "panel_east_azm" => (-180.0, 180.0),
+        "start_azm" | "stop_azm" => (0.0, 360.0),
+        "tau" | "tau_down" => (MIN_TIME_CONSTANT, f64::INFINITY),
+        _ => (0.0, f64::INFINITY),
+    }
+}
+
+/// Returns a tunable parameter value clamped to the range the production model accepts
+///
+/// # Arguments
+///
+/// * 'name' - name of the parameter field
+/// * 'value' - value to clamp
+pub fn clamp_parameter(name: &str, value: f64) -> f64 {
+    let (min, max) = valid_range(name);
+
+    value.clamp(min, max)
+}
+
+/// Returns a mutable reference to a tunable parameter given its name
+///
+/// # Arguments
+///
+/// * 'params' - parameters to look in
+/// * 'name' - name of the parameter field
+pub fn parameter_mut<'a>(params: &'a mut Parameters, name: &str) -> Option<&'a mut f64> {
+    match name {
+        "panel_power" => Some(&mut params.panel_power),
+        "panel_slope" => Some(&mut params.panel_slope),
+        "panel_east_azm" => Some(&mut params.panel_east_azm),
+        "panel_temp_red" => Some(&mut params.panel_temp_red),
+        "tau" => Some(&mut params.tau),
+        "tau_down" => Some(&mut params.tau_down),
+        "k_gain" => Some(&mut params.k_gain),
+        "iam_factor" => Some(&mut params.iam_factor),
+        "start_azm" => Some(&mut params.start_azm),
+        "start_elv" => Some(&mut params.start_elv),
+        "stop_azm" => Some(&mut params.stop_azm),
+        "stop_elv" => Some(&mut params.stop_elv),
+        _ => None,
+    }
+}
+
+/// Returns the value of a tunable parameter given its name
+///
+/// # Arguments
+///
+/// * 'params' - parameters to look in
+/// * 'name' - name of the parameter field
+pub fn parameter(params: &Parameters, name: &str) -> Option<f64> {
+    parameter_mut(&mut params.clone(), name).copied()
+}
+
+/// Returns a copy of the parameters with one tunable parameter set to a new value
+///
+/// # Arguments
+///
+/// * 'params' - parameters to copy
+/// * 'name' - name of the parameter field
+/// * 'value' - new value
+pub fn with_parameter(params: &Parameters, name: &str, value: f64) -> Option<Parameters> {
+    let mut result = params.clone();
+    *parameter_mut(&mut result, name)? = value;
+
+    Some(result)
+}
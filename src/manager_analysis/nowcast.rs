@@ -0,0 +1,103 @@
+use chrono::{DateTime, Local, TimeDelta};
+use serde::Serialize;
+use crate::manager_analysis::errors::AnalysisError;
+use crate::metrics::{actual_energy, estimated_energy};
+use crate::models::{DataItem, Production};
+use crate::serialize_timestamp;
+
+/// Share of the estimate's peak the estimate must average over the lookback for the ratio to be
+/// trusted, before that the model is used as is
+const MIN_LOOKBACK_SHARE: f64 = 0.05;
+
+/// Highest ratio between actual and estimated power used for the correction
+const MAX_RATIO: f64 = 1.5;
+
+/// Estimate for the rest of a day corrected by how actual power has compared with the model lately
+///
+#[derive(Serialize)]
+pub struct Nowcast {
+    /// First minute of the remainder
+    #[serde(with = "serialize_timestamp")]
+    pub now: DateTime<Local>,
+    /// Actual over estimated power during the lookback
+    pub ratio: f64,
+    /// Corrected estimate per minute from now to the end of the day, left out when serialized
+    /// since it is returned as a diagram series
+    #[serde(skip)]
+    pub remainder: Vec<DataItem>,
+    /// Actual energy up to now [kWh]
+    pub energy_so_far: f64,
+    /// Corrected estimated energy from now on [kWh]
+    pub remaining_energy: f64,
+    /// Expected energy for the whole day, actual so far plus corrected remainder [kWh]
+    pub expected_energy: f64,
+    /// Estimated energy for the whole day from the model alone [kWh]
+    pub model_energy: f64,
+}
+
+/// Returns a nowcast for the rest of the day
+///
+/// The ratio between actual and estimated energy over the lookback before now scales the estimate
+/// for the remainder, blended back towards the plain estimate with an e-folding time of `decay`
+/// minutes since clouds that are here now have usually moved on in a few hours.
+///
+/// # Arguments
+///
+/// * 'estimated' - production estimate for the whole day
+/// * 'actual' - actual power so far
+/// * 'now' - minute of the day to nowcast from, if none the minute after the last actual item
+/// * 'lookback' - minutes before now to compare actual with estimated power over
+/// * 'decay' - minutes for the correction to fade to about a third, 0 leaves the estimate as is
+pub fn nowcast(estimated: &Production, actual: &[DataItem], now: Option<usize>, lookback: usize, decay: f64) -> Result<Nowcast, AnalysisError> {
+    let start = estimated.power.first().ok_or("no estimate for the day")?.x;
+    let minutes = estimated.power.len();
+    let minute_of = |d: &DataItem| (d.x - start).num_minutes();
+
+    let now = match now {
+        Some(now) => now,
+        None => actual.iter()
+            .map(minute_of)
+            .filter(|m| *m >= 0)
+            .max()
+            .map_or(0, |m| m as usize + 1),
+    }.min(minutes);
+
+    let so_far = actual.iter()
+        .filter(|d| (0..now as i64).contains(&minute_of(d)))
+        .map(|d| DataItem { x: d.x, y: d.y })
+        .collect::<Vec<DataItem>>();
+
+    // Compare actual with estimated over the same minutes of the lookback
+    let from = now.saturating_sub(lookback) as i64;
+    let (sum_actual, sum_estimated, n) = so_far.iter()
+        .filter(|d| minute_of(d) >= from)
+        .fold((0.0, 0.0, 0), |(a, e, n), d| (a + d.y, e + estimated.power[minute_of(d) as usize].y, n + 1));
+    let peak = estimated.power.iter().map(|d| d.y).fold(0.0, f64::max);
+
+    let ratio = if n > 0 && sum_estimated / n as f64 > MIN_LOOKBACK_SHARE * peak {
+        (sum_actual / sum_estimated).clamp(0.0, MAX_RATIO)
+    } else {
+        1.0
+    };
+
+    let remainder = estimated.power[now..].iter()
+        .enumerate()
+        .map(|(i, d)| {
+            let weight = if decay > 0.0 { (-(i as f64) / decay).exp() } else { 0.0 };
+            DataItem { x: d.x, y: d.y * (1.0 + (ratio - 1.0) * weight) }
+        })
+        .collect::<Vec<DataItem>>();
+
+    let energy_so_far = actual_energy(&so_far);
+    let remaining_energy = estimated_energy(&remainder);
+
+    Ok(Nowcast {
+        now: start + TimeDelta::minutes(now as i64),
+        ratio,
+        remainder,
+        energy_so_far,
+        remaining_energy,
+        expected_energy: energy_so_far + remaining_energy,
+        model_energy: estimated_energy(&estimated.power),
+    })
+}
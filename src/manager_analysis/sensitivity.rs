@@ -0,0 +1,226 @@
+use serde::Serialize;
+use crate::manager_analysis::errors::AnalysisError;
+use crate::manager_analysis::{clamp_parameter, parameter, parameter_mut, with_parameter, TUNABLE};
+use crate::manager_production::get_range_production_cached_if;
+use crate::manager_production::pipeline::ModelChoice;
+use crate::metrics::fit_metrics;
+use crate::models::{DataItem, Parameters};
+
+/// Number of grid levels per parameter in the Morris method
+const MORRIS_LEVELS: f64 = 4.0;
+
+/// Maximum number of Morris trajectories, each runs the model once per tunable parameter and once more
+const MAX_TRAJECTORIES: usize = 50;
+
+/// Daily energy and fit error for one parameter set
+///
+#[derive(Serialize, Clone, Copy)]
+pub struct Outcome {
+    /// Estimated energy [kWh]
+    pub energy: f64,
+    /// Root mean square error against actual power [kW]
+    pub rmse: f64,
+}
+
+/// One-at-a-time effect of perturbing a parameter one step down and one step up, the perturbed
+/// values clamped to the parameter's valid range
+///
+#[derive(Serialize)]
+pub struct OatEffect {
+    pub name: &'static str,
+    pub value: f64,
+    pub step: f64,
+    pub low_value: f64,
+    pub high_value: f64,
+    pub low: Outcome,
+    pub high: Outcome,
+}
+
+/// Morris elementary effects statistics for a parameter, expressed as change per step
+///
+#[derive(Serialize)]
+pub struct MorrisEffect {
+    pub name: &'static str,
+    /// Mean of absolute elementary effects on energy [kWh]
+    pub mu_star_energy: f64,
+    /// Standard deviation of elementary effects on energy [kWh]
+    pub sigma_energy: f64,
+    /// Mean of absolute elementary effects on fit error [kW]
+    pub mu_star_rmse: f64,
+    /// Standard deviation of elementary effects on fit error [kW]
+    pub sigma_rmse: f64,
+}
+
+#[derive(Serialize)]
+pub struct Sensitivity {
+    pub base: Outcome,
+    pub oat: Vec<OatEffect>,
+    pub morris: Vec<MorrisEffect>,
+}
+
+/// Returns the sensitivity of daily energy and fit error to each tunable parameter
+///
+/// Each parameter is perturbed within plus/minus its step (see `TUNABLE`) times `scale`, both one
+/// at a time around the given values and along random Morris trajectories covering all parameters
+/// at once. Perturbed values are clamped to the valid range of the parameter and Morris moves
+/// that clamping leaves without change are left out. All parameter sets are computed in parallel,
+/// solar positions of perturbed panel orientations are not cached.
+///
+/// # Arguments
+///
+/// * 'params' - parameters to analyse around
+/// * 'actual' - actual power for the day
+/// * 'models' - model variant to use for each stage of the production pipeline
+/// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+/// * 'scale' - multiplier on the perturbation steps
+/// * 'trajectories' - number of Morris trajectories, 0 skips the Morris analysis, at most `MAX_TRAJECTORIES`
+pub fn sensitivity(params: &Parameters, actual: &[DataItem], models: &ModelChoice, cache_dir: &str, scale: f64, trajectories: usize) -> Result<Sensitivity, AnalysisError> {
+    if trajectories > MAX_TRAJECTORIES {
+        return Err(format!("too many trajectories, max is {}", MAX_TRAJECTORIES).into());
+    }
+    let steps = TUNABLE.map(|(name, step)| (name, step * scale));
+
+    // One at a time, base followed by low and high per parameter
+    let mut sets: Vec<Parameters> = vec![params.clone()];
+    for (name, step) in steps {
+        let value = parameter(params, name).unwrap();
+        sets.push(with_parameter(params, name, clamp_parameter(name, value - step)).unwrap());
+        sets.push(with_parameter(params, name, clamp_parameter(name, value + step)).unwrap());
+    }
+
+    // Morris trajectories, each a start point followed by one move per parameter
+    let mut rng = SplitMix64(0x5eed);
+    let mut moves: Vec<Vec<usize>> = Vec::new();
+    for _ in 0..trajectories {
+        let mut unit: Vec<f64> = (0..steps.len())
+            .map(|_| (rng.next_below(MORRIS_LEVELS as u64) as f64) / (MORRIS_LEVELS - 1.0))
+            .collect();
+        let mut order: Vec<usize> = (0..steps.len()).collect();
+        for i in (1..order.len()).rev() {
+            order.swap(i, rng.next_below(i as u64 + 1) as usize);
+        }
+
+        sets.push(from_unit(params, &steps, &unit));
+        let mut trajectory: Vec<usize> = Vec::new();
+        for i in order {
+            let delta = MORRIS_LEVELS / (2.0 * (MORRIS_LEVELS - 1.0));
+            let delta = if unit[i] + delta <= 1.0 { delta } else { -delta };
+            unit[i] += delta;
+            sets.push(from_unit(params, &steps, &unit));
+            trajectory.push(i);
+        }
+        moves.push(trajectory);
+    }
+
+    let same_orientation = |p: &Parameters| p.panel_slope == params.panel_slope && p.panel_east_azm == params.panel_east_azm;
+    let outcomes = get_range_production_cached_if(&sets, models, cache_dir, same_orientation)?
+        .iter()
+        .map(|p| {
+            let metrics = fit_metrics(actual, &p.power);
+            Outcome { energy: metrics.estimated_energy, rmse: metrics.rmse }
+        })
+        .collect::<Vec<Outcome>>();
+
+    let oat = steps.iter()
+        .enumerate()
+        .map(|(i, &(name, step))| OatEffect {
+            name,
+            value: parameter(params, name).unwrap(),
+            step,
+            low_value: parameter(&sets[1 + 2 * i], name).unwrap(),
+            high_value: parameter(&sets[2 + 2 * i], name).unwrap(),
+            low: outcomes[1 + 2 * i],
+            high: outcomes[2 + 2 * i],
+        })
+        .collect::<Vec<OatEffect>>();
+
+    // Elementary effects per parameter, scaled to change per step of the clamped parameter values
+    let mut effects: Vec<Vec<(f64, f64)>> = vec![Vec::new(); steps.len()];
+    let mut idx = 1 + 2 * steps.len();
+    for trajectory in moves {
+        for i in trajectory {
+            let (name, step) = steps[i];
+            let change = (parameter(&sets[idx + 1], name).unwrap() - parameter(&sets[idx], name).unwrap()) / step;
+            if change.abs() > f64::EPSILON {
+                let (prev, next) = (outcomes[idx], outcomes[idx + 1]);
+                effects[i].push((
+                    (next.energy - prev.energy) / change,
+                    (next.rmse - prev.rmse) / change,
+                ));
+            }
+            idx += 1;
+        }
+        idx += 1;
+    }
+
+    let morris = if trajectories == 0 {
+        Vec::new()
+    } else {
+        steps.iter()
+            .zip(effects.iter())
+            .map(|(&(name, _), ee)| {
+                let energy = ee.iter().map(|e| e.0).collect::<Vec<f64>>();
+                let rmse = ee.iter().map(|e| e.1).collect::<Vec<f64>>();
+                MorrisEffect {
+                    name,
+                    mu_star_energy: mean_abs(&energy),
+                    sigma_energy: std_dev(&energy),
+                    mu_star_rmse: mean_abs(&rmse),
+                    sigma_rmse: std_dev(&rmse),
+                }
+            })
+            .collect()
+    };
+
+    Ok(Sensitivity { base: outcomes[0], oat, morris })
+}
+
+/// Returns parameters where each tunable parameter is placed within plus/minus its step around
+/// the given value, 0 meaning one step below and 1 one step above, clamped to its valid range
+///
+/// # Arguments
+///
+/// * 'params' - parameters to place around
+/// * 'steps' - tunable parameters and their steps
+/// * 'unit' - position within the range per tunable parameter
+fn from_unit(params: &Parameters, steps: &[(&str, f64)], unit: &[f64]) -> Parameters {
+    let mut result = params.clone();
+    for (&(name, step), u) in steps.iter().zip(unit) {
+        let value = parameter_mut(&mut result, name).unwrap();
+        *value = clamp_parameter(name, *value + step * (2.0 * u - 1.0));
+    }
+
+    result
+}
+
+fn mean_abs(values: &[f64]) -> f64 {
+    values.iter().map(|v| v.abs()).sum::<f64>() / values.len().max(1) as f64
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+/// Small deterministic pseudo random generator so repeated analyses give the same trajectories
+///
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+        z ^ (z >> 31)
+    }
+
+    fn next_below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
@@ -0,0 +1,89 @@
+use serde::Serialize;
+use crate::manager_analysis::errors::AnalysisError;
+use crate::manager_analysis::{parameter, valid_range, with_parameter};
+use crate::manager_production::get_range_production_cached_if;
+use crate::manager_production::pipeline::ModelChoice;
+use crate::metrics::{fit_metrics, pool_metrics, FitMetrics};
+use crate::models::{DataItem, Parameters};
+
+/// Maximum number of parameter values in one sweep
+const MAX_VALUES: usize = 500;
+
+/// Number of days simulated before they are reduced to fit metrics, bounds the productions held
+/// in memory at once
+const BATCH_DAYS: usize = 64;
+
+/// Fit over all swept days for one parameter value
+///
+#[derive(Serialize)]
+pub struct SweepPoint {
+    pub value: f64,
+    pub metrics: FitMetrics,
+}
+
+/// Returns the fit against actual production for each value of one parameter, pooled over
+/// all given days
+///
+/// The swept values must lie within the valid range of the parameter. Values are simulated in
+/// batches of about `BATCH_DAYS` days, and solar positions of swept panel orientations are not
+/// cached.
+///
+/// # Arguments
+///
+/// * 'days' - parameters, one per day
+/// * 'actual' - actual power, one series per day
+/// * 'name' - name of the parameter to sweep
+/// * 'from' - first value
+/// * 'to' - last value, included if reached by whole steps
+/// * 'step' - increment between values
+/// * 'models' - model variant to use for each stage of the production pipeline
+/// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+#[allow(clippy::too_many_arguments)]
+pub fn sweep(days: &[Parameters], actual: &[Vec<DataItem>], name: &str, from: f64, to: f64, step: f64, models: &ModelChoice, cache_dir: &str) -> Result<Vec<SweepPoint>, AnalysisError> {
+    if days.is_empty() || days.len() != actual.len() {
+        return Err("one actual series per day is required".into());
+    }
+    if parameter(&days[0], name).is_none() {
+        return Err(format!("unknown parameter {}", name).into());
+    }
+    if step <= 0.0 || to < from {
+        return Err("step must be > 0 and to must not be less than from".into());
+    }
+
+    let count = ((to - from) / step + 1e-9).floor() as usize + 1;
+    if count > MAX_VALUES {
+        return Err(format!("too many values in sweep, max is {}", MAX_VALUES).into());
+    }
+    let values = (0..count).map(|i| from + step * i as f64).collect::<Vec<f64>>();
+
+    let (min, max) = valid_range(name);
+    if values.iter().any(|v| *v < min || *v > max) {
+        let round = |v: f64| format!("{:.4}", v).trim_end_matches('0').trim_end_matches('.').to_string();
+        let range = if max.is_finite() { format!("within {} and {}", round(min), round(max)) } else { format!("from {} up", round(min)) };
+        return Err(format!("{} must be swept {}", name, range).into());
+    }
+
+    let same_orientation = |p: &Parameters| p.panel_slope == days[0].panel_slope && p.panel_east_azm == days[0].panel_east_azm;
+    let mut result: Vec<SweepPoint> = Vec::new();
+
+    for batch in values.chunks((BATCH_DAYS / days.len()).max(1)) {
+        let sets = batch.iter()
+            .flat_map(|v| days.iter().map(|d| with_parameter(d, name, *v).unwrap()))
+            .collect::<Vec<Parameters>>();
+
+        let estimated = get_range_production_cached_if(&sets, models, cache_dir, same_orientation)?;
+
+        result.extend(batch.iter()
+            .zip(estimated.chunks(days.len()))
+            .map(|(value, productions)| {
+                let per_day = productions.iter()
+                    .zip(actual)
+                    .map(|(p, a)| fit_metrics(a, &p.power))
+                    .collect::<Vec<FitMetrics>>();
+
+                SweepPoint { value: *value, metrics: pool_metrics(&per_day) }
+            }));
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use crate::manager_energy::errors::EnergyError;
+use crate::models::DataItem;
+
+/// Hours per minute, the simulation time step
+const STEP: f64 = 1.0 / 60.0;
+
+/// Home battery specification
+///
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct BatterySpec {
+    /// Usable capacity [kWh]
+    pub capacity: f64,
+    /// Highest charge power [kW]
+    pub max_charge: f64,
+    /// Highest discharge power [kW]
+    pub max_discharge: f64,
+    /// Round-trip efficiency [0,1], split evenly on charge and discharge
+    pub efficiency: f64,
+    /// Lowest and highest state of charge [0,1]
+    pub min_soc: f64,
+    pub max_soc: f64,
+    /// State of charge at midnight of the first day [0,1]
+    pub initial_soc: f64,
+}
+
+impl BatterySpec {
+    /// Returns an error if the specification is not physically meaningful
+    ///
+    pub fn validate(&self) -> Result<(), EnergyError> {
+        if self.capacity < 0.0 || self.max_charge < 0.0 || self.max_discharge < 0.0 {
+            return Err("battery capacity and power limits must not be negative".into());
+        }
+        if !(self.efficiency > 0.0 && self.efficiency <= 1.0) {
+            return Err("round-trip efficiency must be within (0,1]".into());
+        }
+        if !(0.0 <= self.min_soc && self.min_soc <= self.max_soc && self.max_soc <= 1.0) {
+            return Err("state of charge limits must satisfy 0 <= min <= max <= 1".into());
+        }
+        if !(self.min_soc..=self.max_soc).contains(&self.initial_soc) {
+            return Err("initial state of charge must be within the limits".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Energy totals for a day or a range [kWh]
+///
+#[derive(Serialize, Clone, Copy, Default)]
+pub struct EnergySummary {
+    pub production: f64,
+    pub load: f64,
+    pub grid_import: f64,
+    pub grid_export: f64,
+    /// Production used on site, directly or through the battery
+    pub self_consumption: f64,
+    /// Share of production used on site
+    pub self_consumption_rate: f64,
+    /// Share of load covered without grid import
+    pub self_sufficiency: f64,
+}
+
+impl EnergySummary {
+    /// Returns the sum of several summaries with the rates recalculated
+    ///
+    /// # Arguments
+    ///
+    /// * 'summaries' - summaries to add up
+    pub fn total(summaries: &[EnergySummary]) -> Self {
+        let mut result = summaries.iter().fold(EnergySummary::default(), |t, s| EnergySummary {
+            production: t.production + s.production,
+            load: t.load + s.load,
+            grid_import: t.grid_import + s.grid_import,
+            grid_export: t.grid_export + s.grid_export,
+            self_consumption: t.self_consumption + s.self_consumption,
+            ..t
+        });
+        result.set_rates();
+
+        result
+    }
+
+    fn set_rates(&mut self) {
+        self.self_consumption_rate = if self.production > 0.0 { self.self_consumption / self.production } else { 0.0 };
+        self.self_sufficiency = if self.load > 0.0 { 1.0 - self.grid_import / self.load } else { 0.0 };
+    }
+}
+
+/// Battery and grid flows per minute for one day, all power in kW
+///
+#[derive(Serialize)]
+pub struct BatteryDay {
+    /// State of charge at the end of each minute [0,1]
+    pub soc: Vec<DataItem>,
+    /// Battery power, positive when charging
+    pub battery: Vec<DataItem>,
+    pub grid_import: Vec<DataItem>,
+    pub grid_export: Vec<DataItem>,
+    pub self_consumption: Vec<DataItem>,
+    pub summary: EnergySummary,
+}
+
+/// Returns the battery and grid flows for consecutive days, the state of charge carried over from
+/// one day to the next
+///
+/// Surplus production charges the battery and the rest is exported, a deficit is covered by the
+/// battery and the rest is imported, all within the power and state of charge limits.
+///
+/// # Arguments
+///
+/// * 'spec' - battery specification
+/// * 'days' - production per minute and load per minute, one pair per day
+pub fn simulate_battery(spec: &BatterySpec, days: &[(&[DataItem], [f64;1440])]) -> Result<Vec<BatteryDay>, EnergyError> {
+    spec.validate()?;
+
+    let mut stored = spec.initial_soc * spec.capacity;
+    let mut result: Vec<BatteryDay> = Vec::new();
+
+    for (production, load) in days {
+        let (day, end) = simulate_day(spec, production, load, stored);
+        stored = end;
+        result.push(day);
+    }
+
+    Ok(result)
+}
+
+/// Returns the battery and grid flows for one day together with the energy stored at its end
+///
+/// # Arguments
+///
+/// * 'spec' - battery specification
+/// * 'production' - production per minute
+/// * 'load' - load per minute
+/// * 'stored' - energy stored at midnight [kWh]
+fn simulate_day(spec: &BatterySpec, production: &[DataItem], load: &[f64;1440], mut stored: f64) -> (BatteryDay, f64) {
+    let one_way = spec.efficiency.sqrt();
+    let (lowest, highest) = (spec.min_soc * spec.capacity, spec.max_soc * spec.capacity);
+
+    let mut day = BatteryDay {
+        soc: Vec::with_capacity(production.len()),
+        battery: Vec::with_capacity(production.len()),
+        grid_import: Vec::with_capacity(production.len()),
+        grid_export: Vec::with_capacity(production.len()),
+        self_consumption: Vec::with_capacity(production.len()),
+        summary: EnergySummary::default(),
+    };
+
+    for (p, l) in production.iter().zip(load.iter()) {
+        let surplus = p.y - l;
+        let (battery, import, export) = if surplus >= 0.0 {
+            let charge = surplus.min(spec.max_charge).min(((highest - stored) / (STEP * one_way)).max(0.0));
+            stored += charge * STEP * one_way;
+            (charge, 0.0, surplus - charge)
+        } else {
+            let discharge = (-surplus).min(spec.max_discharge).min(((stored - lowest) * one_way / STEP).max(0.0));
+            stored -= discharge * STEP / one_way;
+            (-discharge, -surplus - discharge, 0.0)
+        };
+
+        let self_consumption = p.y - export;
+        let item = |y: f64| DataItem { x: p.x, y };
+        day.soc.push(item(if spec.capacity > 0.0 { stored / spec.capacity } else { 0.0 }));
+        day.battery.push(item(battery));
+        day.grid_import.push(item(import));
+        day.grid_export.push(item(export));
+        day.self_consumption.push(item(self_consumption));
+
+        day.summary.production += p.y * STEP;
+        day.summary.load += l * STEP;
+        day.summary.grid_import += import * STEP;
+        day.summary.grid_export += export * STEP;
+        day.summary.self_consumption += self_consumption * STEP;
+    }
+    day.summary.set_rates();
+
+    (day, stored)
+}
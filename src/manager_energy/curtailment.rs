@@ -0,0 +1,146 @@
+use chrono::{DateTime, Datelike, Local};
+use serde::Serialize;
+use crate::manager_energy::errors::EnergyError;
+use crate::metrics::{actual_energy, estimated_energy};
+use crate::models::DataItem;
+use crate::serialize_timestamp;
+
+/// Energy totals under an export limit [kWh]
+///
+#[derive(Serialize, Clone, Copy, Default)]
+pub struct CurtailmentSummary {
+    pub production: f64,
+    pub load: f64,
+    /// Surplus that would be fed into the grid without a limit
+    pub surplus: f64,
+    /// Energy actually fed into the grid within the limit
+    pub export: f64,
+    /// Surplus above the limit, lost unless used on site
+    pub curtailed: f64,
+}
+
+impl CurtailmentSummary {
+    fn add(&mut self, other: &CurtailmentSummary) {
+        self.production += other.production;
+        self.load += other.load;
+        self.surplus += other.surplus;
+        self.export += other.export;
+        self.curtailed += other.curtailed;
+    }
+}
+
+/// Curtailment for one day
+///
+#[derive(Serialize)]
+pub struct CurtailmentDay {
+    #[serde(with = "serialize_timestamp")]
+    pub date: DateTime<Local>,
+    /// Power above the export limit [kW], left out when serialized since it is returned as a
+    /// diagram series
+    #[serde(skip)]
+    pub curtailed: Vec<DataItem>,
+    pub summary: CurtailmentSummary,
+}
+
+/// Curtailment totals for one calendar month
+///
+#[derive(Serialize)]
+pub struct CurtailmentMonth {
+    pub year: i32,
+    pub month: u32,
+    pub days: usize,
+    pub summary: CurtailmentSummary,
+}
+
+/// Returns the curtailment of a modelled day, the surplus being production less load
+///
+/// # Arguments
+///
+/// * 'production' - production per minute [kW]
+/// * 'load' - load per minute [kW]
+/// * 'limit' - export limit [kW]
+pub fn modelled_curtailment(production: &[DataItem], load: &[f64;1440], limit: f64) -> Result<CurtailmentDay, EnergyError> {
+    let date = production.first().ok_or("no production for the day")?.x;
+    let surplus = production.iter()
+        .zip(load.iter())
+        .map(|(p, l)| DataItem { x: p.x, y: (p.y - l).max(0.0) })
+        .collect::<Vec<DataItem>>();
+    let (export, curtailed) = split_at_limit(&surplus, limit);
+
+    Ok(CurtailmentDay {
+        date,
+        summary: CurtailmentSummary {
+            production: estimated_energy(production),
+            load: load.iter().sum::<f64>() / 60.0,
+            surplus: estimated_energy(&surplus),
+            export: estimated_energy(&export),
+            curtailed: estimated_energy(&curtailed),
+        },
+        curtailed,
+    })
+}
+
+/// Returns the curtailment of a day as recorded by the inverter, had the export limit been in
+/// place, the surplus being the recorded feed-in
+///
+/// Recorded samples are not evenly spaced, so energy is integrated over the sample times.
+///
+/// # Arguments
+///
+/// * 'production' - recorded production [kW]
+/// * 'feed_in' - recorded power fed into the grid [kW]
+/// * 'load' - recorded household load [kW]
+/// * 'limit' - export limit [kW]
+pub fn actual_curtailment(production: &[DataItem], feed_in: &[DataItem], load: &[DataItem], limit: f64) -> Result<CurtailmentDay, EnergyError> {
+    let date = feed_in.first().or(production.first()).ok_or("no recorded data for the day")?.x;
+    let surplus = feed_in.iter()
+        .map(|f| DataItem { x: f.x, y: f.y.max(0.0) })
+        .collect::<Vec<DataItem>>();
+    let (export, curtailed) = split_at_limit(&surplus, limit);
+
+    Ok(CurtailmentDay {
+        date,
+        summary: CurtailmentSummary {
+            production: actual_energy(production),
+            load: actual_energy(load),
+            surplus: actual_energy(&surplus),
+            export: actual_energy(&export),
+            curtailed: actual_energy(&curtailed),
+        },
+        curtailed,
+    })
+}
+
+/// Returns curtailment totals per calendar month for days in order
+///
+/// # Arguments
+///
+/// * 'days' - curtailment per day
+pub fn monthly_curtailment(days: &[CurtailmentDay]) -> Vec<CurtailmentMonth> {
+    let mut result: Vec<CurtailmentMonth> = Vec::new();
+
+    for day in days {
+        let (year, month) = (day.date.year(), day.date.month());
+        match result.last_mut() {
+            Some(m) if m.year == year && m.month == month => {
+                m.days += 1;
+                m.summary.add(&day.summary);
+            },
+            _ => result.push(CurtailmentMonth { year, month, days: 1, summary: day.summary }),
+        }
+    }
+
+    result
+}
+
+/// Returns a surplus split into the part within the export limit and the part above it
+///
+/// # Arguments
+///
+/// * 'surplus' - surplus power [kW]
+/// * 'limit' - export limit [kW]
+fn split_at_limit(surplus: &[DataItem], limit: f64) -> (Vec<DataItem>, Vec<DataItem>) {
+    surplus.iter()
+        .map(|s| (DataItem { x: s.x, y: s.y.min(limit) }, DataItem { x: s.x, y: (s.y - limit).max(0.0) }))
+        .unzip()
+}
@@ -0,0 +1,260 @@
+use std::fs;
+use chrono::{DateTime, Datelike, Local, TimeDelta};
+use serde::{Deserialize, Serialize};
+use crate::manager_energy::battery::BatteryDay;
+use crate::manager_energy::errors::EnergyError;
+use crate::manager_energy::prices::SpotPrices;
+use crate::models::DataItem;
+use crate::serialize_timestamp;
+
+/// Name of the file in the cache directory holding the tariff used when none is given
+const TARIFF_FILE: &str = "tariff.json";
+
+/// Tariff rules on top of the spot price, all amounts per kWh in the currency of the spot prices
+///
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct Tariff {
+    /// Retailer's markup on imported energy
+    pub markup: f64,
+    /// Grid operator's transfer fee on imported energy
+    pub grid_fee: f64,
+    /// Energy tax on imported energy
+    pub energy_tax: f64,
+    /// Value added tax on everything paid for imported energy [0,1]
+    pub vat: f64,
+    /// Grid operator's compensation for exported energy
+    pub export_compensation: f64,
+    /// Tax reduction on exported energy
+    pub tax_reduction: f64,
+}
+
+impl Tariff {
+    /// Returns the tariff stored in the cache directory, or no tariff rules beyond the spot price
+    /// if there is none
+    ///
+    /// # Arguments
+    ///
+    /// * 'cache_dir' - directory holding the tariff file
+    pub fn new(cache_dir: &str) -> Result<Self, EnergyError> {
+        match fs::read_to_string(format!("{}{}", cache_dir, TARIFF_FILE)) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("invalid {}: {}", TARIFF_FILE, e).into()),
+            Err(_) => Ok(Tariff::default()),
+        }
+    }
+
+    /// Returns the price paid per imported kWh
+    ///
+    /// # Arguments
+    ///
+    /// * 'spot' - spot price per kWh
+    pub fn import_price(&self, spot: f64) -> f64 {
+        (spot + self.markup + self.grid_fee + self.energy_tax) * (1.0 + self.vat)
+    }
+
+    /// Returns the price received per exported kWh
+    ///
+    /// # Arguments
+    ///
+    /// * 'spot' - spot price per kWh
+    pub fn export_price(&self, spot: f64) -> f64 {
+        spot + self.export_compensation + self.tax_reduction
+    }
+}
+
+/// Power flows per minute of one day [kW]
+///
+pub struct GridFlows {
+    pub time: Vec<DateTime<Local>>,
+    pub production: Vec<f64>,
+    pub load: Vec<f64>,
+    pub grid_import: Vec<f64>,
+    pub grid_export: Vec<f64>,
+}
+
+impl GridFlows {
+    /// Returns the flows of production used directly on site, the rest exported
+    ///
+    /// # Arguments
+    ///
+    /// * 'production' - production per minute
+    /// * 'load' - load per minute
+    pub fn direct(production: &[DataItem], load: &[f64;1440]) -> Self {
+        Self {
+            time: production.iter().map(|p| p.x).collect(),
+            production: production.iter().map(|p| p.y).collect(),
+            load: load[..production.len().min(1440)].to_vec(),
+            grid_import: production.iter().zip(load.iter()).map(|(p, l)| (l - p.y).max(0.0)).collect(),
+            grid_export: production.iter().zip(load.iter()).map(|(p, l)| (p.y - l).max(0.0)).collect(),
+        }
+    }
+
+    /// Returns the flows with a home battery in between
+    ///
+    /// # Arguments
+    ///
+    /// * 'production' - production per minute
+    /// * 'load' - load per minute
+    /// * 'battery' - the simulated battery day
+    pub fn with_battery(production: &[DataItem], load: &[f64;1440], battery: &BatteryDay) -> Self {
+        Self {
+            time: production.iter().map(|p| p.x).collect(),
+            production: production.iter().map(|p| p.y).collect(),
+            load: load[..production.len().min(1440)].to_vec(),
+            grid_import: battery.grid_import.iter().map(|d| d.y).collect(),
+            grid_export: battery.grid_export.iter().map(|d| d.y).collect(),
+        }
+    }
+
+    /// Returns the flows recorded by the inverter, interpolated to every minute of the day
+    ///
+    /// # Arguments
+    ///
+    /// * 'production' - recorded production
+    /// * 'feed_in' - recorded power fed into the grid
+    /// * 'load' - recorded household load
+    pub fn recorded(production: &[DataItem], feed_in: &[DataItem], load: &[DataItem]) -> Result<Self, EnergyError> {
+        let first = production.first().ok_or("no recorded production for the day")?.x;
+        let start = first.date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(Local).earliest())
+            .ok_or("invalid start of day")?;
+        let time = (0..1440).map(|m| start + TimeDelta::minutes(m)).collect::<Vec<DateTime<Local>>>();
+
+        let production = interpolate(production, &time);
+        let grid_export = interpolate(feed_in, &time).into_iter().map(|f| f.max(0.0)).collect::<Vec<f64>>();
+        let load = interpolate(load, &time);
+        let grid_import = production.iter()
+            .zip(grid_export.iter())
+            .zip(load.iter())
+            .map(|((p, e), l)| (l - (p - e)).max(0.0))
+            .collect();
+
+        Ok(Self { time, production, load, grid_import, grid_export })
+    }
+}
+
+/// Energy and money for a day or a month, energy in kWh and money in the spot price currency
+///
+#[derive(Serialize, Clone, Copy, Default)]
+pub struct ValueSummary {
+    pub production: f64,
+    pub self_consumption: f64,
+    pub grid_import: f64,
+    pub grid_export: f64,
+    /// Import cost avoided by using production on site
+    pub savings: f64,
+    /// Paid for exported energy
+    pub export_revenue: f64,
+    /// Cost of imported energy
+    pub import_cost: f64,
+    /// What production is worth, savings plus export revenue
+    pub value: f64,
+}
+
+impl ValueSummary {
+    pub fn add(&mut self, other: &ValueSummary) {
+        self.production += other.production;
+        self.self_consumption += other.self_consumption;
+        self.grid_import += other.grid_import;
+        self.grid_export += other.grid_export;
+        self.savings += other.savings;
+        self.export_revenue += other.export_revenue;
+        self.import_cost += other.import_cost;
+        self.value += other.value;
+    }
+}
+
+#[derive(Serialize)]
+pub struct ValueDay {
+    #[serde(with = "serialize_timestamp")]
+    pub date: DateTime<Local>,
+    pub summary: ValueSummary,
+}
+
+#[derive(Serialize)]
+pub struct ValueMonth {
+    pub year: i32,
+    pub month: u32,
+    pub days: usize,
+    pub summary: ValueSummary,
+}
+
+/// Returns what a day of production is worth at spot prices under a tariff
+///
+/// Energy used on site is valued at the import price it replaces and exported energy at the
+/// export price, both following the spot price of each minute.
+///
+/// # Arguments
+///
+/// * 'flows' - power flows per minute of the day
+/// * 'prices' - spot prices
+/// * 'tariff' - tariff rules
+pub fn value_day(flows: &GridFlows, prices: &SpotPrices, tariff: &Tariff) -> Result<ValueDay, EnergyError> {
+    let date = *flows.time.first().ok_or("no power flows for the day")?;
+    let step = 1.0 / 60.0;
+    let mut summary = ValueSummary::default();
+
+    for (i, t) in flows.time.iter().enumerate() {
+        let spot = prices.price_at(*t).ok_or(format!("no spot price for {}", t.format("%Y-%m-%d %H:%M")))?;
+        let (import_price, export_price) = (tariff.import_price(spot), tariff.export_price(spot));
+        let self_consumption = (flows.load[i] - flows.grid_import[i]).max(0.0);
+
+        summary.production += flows.production[i] * step;
+        summary.self_consumption += self_consumption * step;
+        summary.grid_import += flows.grid_import[i] * step;
+        summary.grid_export += flows.grid_export[i] * step;
+        summary.savings += self_consumption * import_price * step;
+        summary.export_revenue += flows.grid_export[i] * export_price * step;
+        summary.import_cost += flows.grid_import[i] * import_price * step;
+    }
+    summary.value = summary.savings + summary.export_revenue;
+
+    Ok(ValueDay { date, summary })
+}
+
+/// Returns value totals per calendar month for days in order
+///
+/// # Arguments
+///
+/// * 'days' - value per day
+pub fn monthly_value(days: &[ValueDay]) -> Vec<ValueMonth> {
+    let mut result: Vec<ValueMonth> = Vec::new();
+
+    for day in days {
+        let (year, month) = (day.date.year(), day.date.month());
+        match result.last_mut() {
+            Some(m) if m.year == year && m.month == month => {
+                m.days += 1;
+                m.summary.add(&day.summary);
+            },
+            _ => result.push(ValueMonth { year, month, days: 1, summary: day.summary }),
+        }
+    }
+
+    result
+}
+
+/// Returns values of samples ordered in time linearly interpolated at the given times, held
+/// before the first and after the last sample
+///
+/// # Arguments
+///
+/// * 'samples' - the samples
+/// * 'time' - times to interpolate at
+fn interpolate(samples: &[DataItem], time: &[DateTime<Local>]) -> Vec<f64> {
+    time.iter()
+        .map(|t| {
+            let after = samples.partition_point(|s| s.x <= *t);
+            match (after.checked_sub(1).map(|i| &samples[i]), samples.get(after)) {
+                (Some(a), Some(b)) => {
+                    let span = (b.x - a.x).num_seconds().max(1) as f64;
+                    a.y + (b.y - a.y) * (*t - a.x).num_seconds() as f64 / span
+                },
+                (Some(a), None) => a.y,
+                (None, Some(b)) => b.y,
+                (None, None) => 0.0,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,16 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+#[derive(Debug)]
+pub struct EnergyError(pub String);
+impl fmt::Display for EnergyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "EnergyError: {}", self.0)
+    }
+}
+impl From<&str> for EnergyError {
+    fn from(e: &str) -> Self { EnergyError(e.to_string()) }
+}
+impl From<String> for EnergyError {
+    fn from(e: String) -> Self { EnergyError(e) }
+}
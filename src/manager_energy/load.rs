@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use crate::manager_energy::errors::EnergyError;
+
+/// Typical household load per hour of the day [kW], a night base load with morning and evening
+/// peaks
+const TYPICAL_LOAD: [f64;24] = [
+    0.3, 0.3, 0.3, 0.3, 0.3, 0.4, 0.8, 1.2, 0.9, 0.6, 0.5, 0.5,
+    0.6, 0.5, 0.5, 0.6, 0.9, 1.4, 1.6, 1.3, 1.0, 0.8, 0.5, 0.4,
+];
+
+/// Household load as mean power per hour of the day [kW], the same every day
+///
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LoadProfile {
+    pub hourly: Vec<f64>,
+}
+
+impl Default for LoadProfile {
+    fn default() -> Self {
+        Self { hourly: TYPICAL_LOAD.to_vec() }
+    }
+}
+
+impl LoadProfile {
+    /// Returns the load per minute of the day [kW]
+    ///
+    pub fn per_minute(&self) -> Result<[f64;1440], EnergyError> {
+        if self.hourly.len() != 24 {
+            return Err("a load profile must have 24 hourly values".into());
+        }
+        if self.hourly.iter().any(|l| *l < 0.0) {
+            return Err("load must not be negative".into());
+        }
+
+        let mut result: [f64;1440] = [0.0; 1440];
+        result.iter_mut()
+            .enumerate()
+            .for_each(|(m, l)| *l = self.hourly[m / 60]);
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod battery;
+pub mod curtailment;
+pub mod economics;
+pub mod errors;
+pub mod load;
+pub mod prices;
+pub mod surplus;
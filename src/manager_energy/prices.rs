@@ -0,0 +1,115 @@
+use std::fs;
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta};
+use serde::Deserialize;
+use crate::manager_energy::errors::EnergyError;
+
+/// Names of the files in the cache directory holding spot prices, either or both may be present
+const PRICES_CSV: &str = "spot_prices.csv";
+const PRICES_JSON: &str = "spot_prices.json";
+
+/// Longest period a price is valid for, a gap beyond it means prices are missing
+const MAX_PERIOD: TimeDelta = TimeDelta::hours(1);
+
+/// Local time formats accepted in price files besides RFC 3339
+const TIME_FORMATS: [&str; 4] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%d.%m.%Y %H:%M"];
+
+/// One spot price as stored in a JSON price file
+///
+#[derive(Deserialize)]
+struct PriceItem {
+    start: String,
+    price: f64,
+}
+
+/// Day-ahead spot prices, hourly or quarter-hourly, for one bidding area
+///
+/// Price files hold the price per MWh as published by Nord Pool. The CSV file has a start time
+/// and a price per line, separated by comma or, with decimal comma, by semicolon, lines that do
+/// not parse such as headers are skipped. Nord Pool style periods like `02.06.2025 00:00 - 01:00`
+/// are read by their start. The JSON file is an array of `{"start": ..., "price": ...}`.
+///
+pub struct SpotPrices {
+    /// Start of each price period and price per kWh, ordered by start
+    prices: Vec<(DateTime<Local>, f64)>,
+}
+
+impl SpotPrices {
+    /// Returns spot prices read from the price files in the cache directory
+    ///
+    /// # Arguments
+    ///
+    /// * 'cache_dir' - directory holding the price files
+    pub fn new(cache_dir: &str) -> Result<Self, EnergyError> {
+        let mut prices: Vec<(DateTime<Local>, f64)> = Vec::new();
+
+        if let Ok(csv) = fs::read_to_string(format!("{}{}", cache_dir, PRICES_CSV)) {
+            prices.extend(csv.lines().filter_map(parse_csv_line));
+        }
+        if let Ok(json) = fs::read_to_string(format!("{}{}", cache_dir, PRICES_JSON)) {
+            let items: Vec<PriceItem> = serde_json::from_str(&json)
+                .map_err(|e| format!("invalid {}: {}", PRICES_JSON, e))?;
+            for item in items {
+                let start = parse_time(&item.start).ok_or(format!("invalid time {} in {}", item.start, PRICES_JSON))?;
+                prices.push((start, item.price / 1000.0));
+            }
+        }
+        if prices.is_empty() {
+            return Err(format!("no spot prices in {} or {}", PRICES_CSV, PRICES_JSON).into());
+        }
+
+        prices.sort_by_key(|(t, _)| *t);
+        prices.dedup_by_key(|(t, _)| *t);
+
+        Ok(Self { prices })
+    }
+
+    /// Returns the spot price per kWh at a point in time, if known
+    ///
+    /// # Arguments
+    ///
+    /// * 'date_time' - point in time
+    pub fn price_at(&self, date_time: DateTime<Local>) -> Option<f64> {
+        let index = self.prices.partition_point(|(t, _)| *t <= date_time).checked_sub(1)?;
+        let (start, price) = self.prices[index];
+        let end = self.prices.get(index + 1).map_or(start + MAX_PERIOD, |(t, _)| (*t).min(start + MAX_PERIOD));
+
+        (date_time < end).then_some(price)
+    }
+}
+
+/// Returns the start time and price per kWh of a CSV line, if it holds one
+///
+/// # Arguments
+///
+/// * 'line' - line of a CSV price file
+fn parse_csv_line(line: &str) -> Option<(DateTime<Local>, f64)> {
+    let (time, price) = if line.contains(';') {
+        let mut fields = line.split(';');
+        (fields.next()?, fields.next()?.trim().replace(',', "."))
+    } else {
+        let mut fields = line.split(',');
+        (fields.next()?, fields.next()?.trim().to_string())
+    };
+    let price = price.trim_matches('"').replace(' ', "").parse::<f64>().ok()?;
+
+    Some((parse_time(time)?, price / 1000.0))
+}
+
+/// Returns a point in time from RFC 3339 or a local time in one of the accepted formats, a
+/// period is read by its start
+///
+/// # Arguments
+///
+/// * 'text' - the time as text
+fn parse_time(text: &str) -> Option<DateTime<Local>> {
+    let text = text.trim().trim_matches('"');
+    let text = text.split(" - ").next()?;
+
+    if let Ok(t) = DateTime::parse_from_rfc3339(text) {
+        return Some(t.with_timezone(&Local));
+    }
+
+    TIME_FORMATS.iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+        .and_then(|t| t.and_local_timezone(Local).earliest())
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Local, TimeDelta, Timelike};
+use serde::Serialize;
+use crate::manager_energy::errors::EnergyError;
+use crate::models::DataItem;
+use crate::serialize_timestamp;
+
+/// A continuous stretch of time where production exceeds load by at least a threshold
+///
+#[derive(Serialize)]
+pub struct SurplusWindow {
+    #[serde(with = "serialize_timestamp")]
+    pub start: DateTime<Local>,
+    /// End of the window, the minute after its last
+    #[serde(with = "serialize_timestamp")]
+    pub end: DateTime<Local>,
+    pub minutes: usize,
+    /// Surplus energy within the window [kWh]
+    pub energy: f64,
+    /// Mean and lowest surplus power within the window [kW]
+    pub mean_surplus: f64,
+    pub min_surplus: f64,
+}
+
+/// Returns surplus power per minute, production less load, where the load is looked up by the
+/// minute of day of each production item
+///
+/// # Arguments
+///
+/// * 'power' - production per minute, possibly only part of a day
+/// * 'load' - load per minute of the day
+pub fn surplus(power: &[DataItem], load: &[f64;1440]) -> Vec<DataItem> {
+    power.iter()
+        .map(|p| {
+            let minute = (p.x.hour() * 60 + p.x.minute()) as usize;
+            DataItem { x: p.x, y: p.y - load[minute.min(1439)] }
+        })
+        .collect()
+}
+
+/// Returns the windows where surplus power stays at or above a threshold for at least a minimum
+/// duration, such as 3 kW for an hour, in order of time
+///
+/// # Arguments
+///
+/// * 'surplus' - surplus power per minute
+/// * 'threshold' - lowest surplus power [kW]
+/// * 'min_minutes' - shortest window in minutes
+pub fn surplus_windows(surplus: &[DataItem], threshold: f64, min_minutes: usize) -> Result<Vec<SurplusWindow>, EnergyError> {
+    if min_minutes == 0 {
+        return Err("minimum window duration must be at least one minute".into());
+    }
+
+    let mut result: Vec<SurplusWindow> = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for i in 0..=surplus.len() {
+        let above = surplus.get(i).is_some_and(|s| s.y >= threshold);
+        match (above, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                if i - s >= min_minutes {
+                    result.push(window(&surplus[s..i]));
+                }
+                start = None;
+            },
+            _ => (),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns a window covering all given minutes
+///
+/// # Arguments
+///
+/// * 'minutes' - surplus power per minute within the window, at least one
+fn window(minutes: &[DataItem]) -> SurplusWindow {
+    let energy = minutes.iter().map(|m| m.y).sum::<f64>() / 60.0;
+
+    SurplusWindow {
+        start: minutes[0].x,
+        end: minutes[minutes.len() - 1].x + TimeDelta::minutes(1),
+        minutes: minutes.len(),
+        energy,
+        mean_surplus: energy * 60.0 / minutes.len() as f64,
+        min_surplus: minutes.iter().map(|m| m.y).fold(f64::INFINITY, f64::min),
+    }
+}
@@ -17,6 +17,8 @@ use crate::models::DataItem;
 
 const REQUEST_DOMAIN: &str = "https://www.foxesscloud.com";
 const CACHE_PREFIX: &str = "pv";
+const FEED_IN_CACHE_PREFIX: &str = "feedin";
+const LOAD_CACHE_PREFIX: &str = "load";
 
 pub struct Fox {
     api_key: String,
@@ -47,7 +49,39 @@ impl Fox {
     /// * 'date_time' - date to get history for
     /// * 'cache_dir' - directory to store/fetch existing date to/from
     pub async fn get_device_history_data(&self, date_time: DateTime<Local>, cache_dir: &str) -> Result<Vec<DataItem>, FoxError> {
-        if let Some(result) = read_cache_data(cache_dir, CACHE_PREFIX, date_time).await? {
+        self.get_variable_history(date_time, cache_dir, "pvPower", CACHE_PREFIX).await
+    }
+
+    /// Obtain power fed into the grid from the inverter's history
+    ///
+    /// # Arguments
+    ///
+    /// * 'date_time' - date to get history for
+    /// * 'cache_dir' - directory to store/fetch existing date to/from
+    pub async fn get_feed_in_history_data(&self, date_time: DateTime<Local>, cache_dir: &str) -> Result<Vec<DataItem>, FoxError> {
+        self.get_variable_history(date_time, cache_dir, "feedinPower", FEED_IN_CACHE_PREFIX).await
+    }
+
+    /// Obtain household load from the inverter's history
+    ///
+    /// # Arguments
+    ///
+    /// * 'date_time' - date to get history for
+    /// * 'cache_dir' - directory to store/fetch existing date to/from
+    pub async fn get_load_history_data(&self, date_time: DateTime<Local>, cache_dir: &str) -> Result<Vec<DataItem>, FoxError> {
+        self.get_variable_history(date_time, cache_dir, "loadsPower", LOAD_CACHE_PREFIX).await
+    }
+
+    /// Obtain history data for one variable from the inverter, each variable cached separately
+    ///
+    /// # Arguments
+    ///
+    /// * 'date_time' - date to get history for
+    /// * 'cache_dir' - directory to store/fetch existing date to/from
+    /// * 'variable' - name of the variable in the FoxESS API
+    /// * 'prefix' - cache file prefix for the variable
+    async fn get_variable_history(&self, date_time: DateTime<Local>, cache_dir: &str, variable: &str, prefix: &str) -> Result<Vec<DataItem>, FoxError> {
+        if let Some(result) = read_cache_data(cache_dir, prefix, date_time).await? {
             return Ok(result);
         }
         
@@ -58,21 +92,19 @@ impl Fox {
 
         let req = RequestDeviceHistoryData {
             sn: self.sn.clone(),
-            variables: ["pvPower"]
-                .iter().map(|s| s.to_string())
-                .collect::<Vec<String>>(),
+            variables: vec![variable.to_string()],
             begin: start.timestamp_millis(),
             end: end.timestamp_millis(),
         };
 
         let req_json = serde_json::to_string(&req)?;
 
-        let json = self.post_request(&path, req_json).await?;
+        let json = self.post_request(path, req_json).await?;
 
         let fox_data: DeviceHistoryResult = serde_json::from_str(&json)?;
-        let device_history = transform_history_data(fox_data.result)?;
+        let device_history = transform_history_data(fox_data.result, variable)?;
 
-        store_cache_data(cache_dir, CACHE_PREFIX, date_time, &device_history).await?;
+        store_cache_data(cache_dir, prefix, date_time, &device_history).await?;
         
         Ok(device_history)
     }
@@ -89,7 +121,7 @@ impl Fox {
         let url = format!("{}{}", REQUEST_DOMAIN, path);
 
         //let mut req = self.client.post(url);
-        let headers = self.generate_headers(&path, Some(vec!(("Content-Type", "application/json"))));
+        let headers = self.generate_headers(path, Some(vec!(("Content-Type", "application/json"))));
 
         let req = self.client.post(url)
             .headers(headers)
@@ -147,11 +179,12 @@ impl Fox {
 /// # Arguments
 ///
 /// * 'input' - the data to transform
-fn transform_history_data(input: Vec<DeviceHistoryData>) -> Result<Vec<DataItem>, FoxError> {
+/// * 'variable' - name of the variable to pick from the data
+fn transform_history_data(input: Vec<DeviceHistoryData>, variable: &str) -> Result<Vec<DataItem>, FoxError> {
     let mut result: Vec<DataItem> = Vec::new();
 
     for set in &input[0].data_set {
-        if set.variable == "pvPower" {
+        if set.variable == variable {
             for data in &set.data {
                 let timestamp = NaiveDateTime::parse_from_str(&data.time, "%Y-%m-%d %H:%M:%S %Z")?
                     .and_local_timezone(Local).unwrap();
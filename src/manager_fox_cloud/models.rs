@@ -40,9 +40,7 @@ where D: Deserializer<'de> {
     let v = Value::deserialize(deserializer)?;
     let x = v.as_f64()
         .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
-        .ok_or_else(|| Error::custom("non-f64"))?
-        .try_into()
-        .map_err(|_| Error::custom("overflow"))?;
+        .ok_or_else(|| Error::custom("non-f64"))?;
 
     Ok(x)
 }
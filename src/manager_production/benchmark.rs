@@ -0,0 +1,169 @@
+use std::time::Instant;
+use serde::Serialize;
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::SolarPositions;
+use crate::manager_production::pipeline::{solar_position_model, ModelChoice, Pipeline, SolarPositionChoice};
+use crate::manager_production::{day_power_from_positions, day_start};
+use crate::metrics::estimated_energy;
+use crate::models::Parameters;
+use crate::parallel::par_map;
+
+/// Timing and deviation from per minute SPA for one solar position model over a set of days
+///
+#[derive(Serialize)]
+pub struct SolarBenchmark {
+    pub model: String,
+    /// Time spent in the solar position stage [s]
+    pub solar_seconds: f64,
+    /// Time spent in the whole pipeline [s]
+    pub total_seconds: f64,
+    /// Solar position stage speedup compared to per minute SPA
+    pub speedup: f64,
+    pub max_zenith_error: f64,
+    pub mean_zenith_error: f64,
+    pub max_azimuth_error: f64,
+    pub max_incidence_error: f64,
+    /// Estimated energy over all days [kWh]
+    pub energy: f64,
+    /// Estimated energy relative to energy using per minute SPA minus one
+    pub energy_error: f64,
+}
+
+/// Timing and deviation from the reference for one day
+///
+struct DayBench {
+    /// Solar positions of the day, kept for the reference model only
+    positions: Option<SolarPositions>,
+    solar_seconds: f64,
+    total_seconds: f64,
+    energy: f64,
+    max_zenith_error: f64,
+    max_azimuth_error: f64,
+    max_incidence_error: f64,
+    zenith_error_sum: f64,
+    minutes: usize,
+}
+
+/// Runs the production pipeline, without solar position caching, for each solar position model
+/// over all given days on the production worker threads and compares the result with per minute
+/// SPA, times are summed over the days whichever thread ran them
+///
+/// # Arguments
+///
+/// * 'params' - parameters, one per day
+/// * 'models' - model variant for the stages after the solar position stage
+/// * 'cache_dir' - cache directory, not used for solar positions
+/// * 'choices' - solar position models to benchmark, SPA is always run first as reference
+pub fn solar_benchmark(params: &[Parameters], models: &ModelChoice, cache_dir: &str, choices: &[SolarPositionChoice]) -> Result<Vec<SolarBenchmark>, ProdError> {
+    let mut reference: Vec<SolarPositions> = Vec::new();
+    let mut reference_seconds = 0.0;
+    let mut reference_energy = 0.0;
+    let mut result: Vec<SolarBenchmark> = Vec::new();
+    let days = params.iter().enumerate().collect::<Vec<(usize, &Parameters)>>();
+
+    let choices = [SolarPositionChoice::Spa].into_iter()
+        .chain(choices.iter().copied().filter(|c| *c != SolarPositionChoice::Spa));
+
+    for choice in choices {
+        let pipeline = Pipeline { solar_position: solar_position_model(choice), ..Pipeline::new(models, cache_dir) };
+        let day_benches = par_map(&days, |(day, p)| bench_day(p, &pipeline, reference.get(*day)))
+            .into_iter()
+            .collect::<Result<Vec<DayBench>, ProdError>>()?;
+
+        let mut bench = SolarBenchmark {
+            model: pipeline.solar_position.id(),
+            solar_seconds: 0.0,
+            total_seconds: 0.0,
+            speedup: 1.0,
+            max_zenith_error: 0.0,
+            mean_zenith_error: 0.0,
+            max_azimuth_error: 0.0,
+            max_incidence_error: 0.0,
+            energy: 0.0,
+            energy_error: 0.0,
+        };
+        let mut zenith_error_sum = 0.0;
+        let mut minutes: usize = 0;
+
+        for day in day_benches {
+            bench.solar_seconds += day.solar_seconds;
+            bench.total_seconds += day.total_seconds;
+            bench.energy += day.energy;
+            bench.max_zenith_error = bench.max_zenith_error.max(day.max_zenith_error);
+            bench.max_azimuth_error = bench.max_azimuth_error.max(day.max_azimuth_error);
+            bench.max_incidence_error = bench.max_incidence_error.max(day.max_incidence_error);
+            zenith_error_sum += day.zenith_error_sum;
+            minutes += day.minutes;
+
+            if let Some(sp) = day.positions {
+                reference.push(sp);
+            }
+        }
+
+        if choice == SolarPositionChoice::Spa {
+            reference_seconds = bench.solar_seconds;
+            reference_energy = bench.energy;
+        } else {
+            bench.speedup = reference_seconds / bench.solar_seconds.max(f64::EPSILON);
+            bench.mean_zenith_error = zenith_error_sum / minutes.max(1) as f64;
+            if reference_energy > 0.0 {
+                bench.energy_error = bench.energy / reference_energy - 1.0;
+            }
+        }
+
+        result.push(bench);
+    }
+
+    Ok(result)
+}
+
+/// Runs the production pipeline for one day and compares its solar positions with the reference
+///
+/// # Arguments
+///
+/// * 'params' - parameters of the day
+/// * 'pipeline' - stage implementations to run
+/// * 'reference' - per minute SPA positions of the day, none when running the reference itself
+fn bench_day(params: &Parameters, pipeline: &Pipeline, reference: Option<&SolarPositions>) -> Result<DayBench, ProdError> {
+    let date_time = day_start(params);
+
+    let start = Instant::now();
+    let sp = pipeline.solar_position.solar_positions(date_time, params)?;
+    let solar_done = Instant::now();
+    let production = day_power_from_positions(params, date_time, pipeline, &sp)?;
+
+    let mut day = DayBench {
+        positions: None,
+        solar_seconds: (solar_done - start).as_secs_f64(),
+        total_seconds: start.elapsed().as_secs_f64(),
+        energy: estimated_energy(&production.power),
+        max_zenith_error: 0.0,
+        max_azimuth_error: 0.0,
+        max_incidence_error: 0.0,
+        zenith_error_sum: 0.0,
+        minutes: 0,
+    };
+
+    let Some(r) = reference else {
+        day.positions = Some(sp);
+        return Ok(day);
+    };
+
+    for m in 0..1440 {
+        if r.elevation[m] <= 0.0 || sp.elevation[m] <= 0.0 {
+            continue;
+        }
+        let zenith_error = (sp.zenith[m] - r.zenith[m]).abs();
+        let azimuth_error = ((sp.azimuth[m] - r.azimuth[m] + 540.0).rem_euclid(360.0) - 180.0).abs();
+        let incidence_error = (sp.incidence_east[m] - r.incidence_east[m]).abs()
+            .max((sp.incidence_west[m] - r.incidence_west[m]).abs());
+
+        day.max_zenith_error = day.max_zenith_error.max(zenith_error);
+        day.max_azimuth_error = day.max_azimuth_error.max(azimuth_error);
+        day.max_incidence_error = day.max_incidence_error.max(incidence_error);
+        day.zenith_error_sum += zenith_error;
+        day.minutes += 1;
+    }
+
+    Ok(day)
+}
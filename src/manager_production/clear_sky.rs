@@ -0,0 +1,56 @@
+use crate::manager_production::models::SolarPositions;
+use crate::manager_production::pipeline::ClearSkyModel;
+
+/// Air mass clear sky model with a flattened shape fitted to our site
+///
+pub struct AirMass;
+
+impl ClearSkyModel for AirMass {
+    fn sun_intensity(&self, solar_positions: &SolarPositions) -> [f64;1440] {
+        sun_intensity_factor(&solar_positions.zenith, 0.6)
+    }
+}
+
+/// Air mass clear sky model with the originally proposed shape by Meinel & Meinel
+///
+pub struct Meinel;
+
+impl ClearSkyModel for Meinel {
+    fn sun_intensity(&self, solar_positions: &SolarPositions) -> [f64;1440] {
+        sun_intensity_factor(&solar_positions.zenith, 0.678)
+    }
+}
+
+/// Returns percentage of sun intensity in relation to intensity external to the earth's atmosphere.
+/// The algorithm (https://en.wikipedia.org/wiki/Air_mass_(solar_energy)) is based on the
+/// air mass effect and then approximated to sun intensity.
+///
+/// # Arguments
+///
+/// * 'zenith_angle' - sun angle in relation to sun zenith (expected to be clamped between 0 and 90)
+/// * 'shape' - exponent on air mass, originally proposed as 0.678
+fn sun_intensity_factor(zenith_angle: &[f64;1440], shape: f64) -> [f64;1440] {
+
+    // The ratio between the earth's radius (6371 km) and the effective height of the atmosphere (9 km)
+    const R: f64 = 708.0;
+
+    // Intensity external to earths atmosphere
+    const I_0: f64 = 1353.0;
+
+    let mut result: [f64;1440] = [0.0; 1440];
+
+    for i in 0..1440usize {
+        let zenith_cos = zenith_angle[i].to_radians().cos();
+        let enumerator = 2.0 * R + 1.0;
+        let denominator = ((R * zenith_cos).powf(2.0) + 2.0 * R + 1.0).sqrt() + R * zenith_cos;
+        let am = enumerator / denominator;
+
+        // Approximation of sun intensity including a 10% diffuse contribution
+        let intensity = 1.1 * I_0 * 0.7f64.powf(am.powf(shape));
+
+        // Percentage of intensity compared to I_0
+        result[i] = intensity / I_0;
+    }
+
+    result
+}
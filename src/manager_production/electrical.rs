@@ -0,0 +1,37 @@
+use crate::manager_production::pipeline::{AcModel, DcModel};
+use crate::models::Parameters;
+
+/// Panel power reduced linearly by `panel_temp_red` percent per degree above 25 °C
+///
+pub struct LinearTemperature;
+
+impl DcModel for LinearTemperature {
+    fn dc_power(&self, params: &Parameters, panels: f64, irradiance: f64, panel_temperature: f64) -> f64 {
+        let temp_red = 1.0 - (panel_temperature.max(0.0) - 25.0) * params.panel_temp_red / 100.0;
+
+        params.panel_power * panels * irradiance * temp_red
+    }
+}
+
+/// Ideal inverter, DC power is delivered as is
+///
+pub struct Passthrough;
+
+impl AcModel for Passthrough {
+    fn ac_power(&self, dc_power: f64) -> f64 {
+        dc_power / 1000.0
+    }
+}
+
+/// Inverter with a constant conversion efficiency and clipping at its maximum output
+///
+pub struct Inverter {
+    pub max_power: f64,
+    pub efficiency: f64,
+}
+
+impl AcModel for Inverter {
+    fn ac_power(&self, dc_power: f64) -> f64 {
+        (dc_power * self.efficiency / 1000.0).min(self.max_power)
+    }
+}
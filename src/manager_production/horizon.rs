@@ -0,0 +1,131 @@
+use std::fs;
+use serde::{Deserialize, Serialize};
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::{Shading, SolarPositions};
+use crate::manager_production::pipeline::ShadingModel;
+use crate::models::Parameters;
+
+/// Name of the horizon mask file in the cache directory
+const HORIZON_FILE: &str = "horizon.json";
+
+/// Horizon elevation and transmittance below it per azimuth bin
+///
+/// Bin `i` covers azimuths from `i * bin_width` to `(i + 1) * bin_width` degrees, eastward from
+/// north. Below the horizon elevation the beam is multiplied by the transmittance, 0 for solid
+/// obstacles and in between for leafy trees.
+///
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HorizonMask {
+    pub bin_width: f64,
+    pub elevation: Vec<f64>,
+    pub transmittance: Vec<f64>,
+}
+
+impl HorizonMask {
+    /// Returns the beam factor for a sun position
+    ///
+    /// # Arguments
+    ///
+    /// * 'azimuth' - sun azimuth, eastward from north, in degrees
+    /// * 'elevation' - sun elevation in degrees
+    pub fn beam_factor(&self, azimuth: f64, elevation: f64) -> f64 {
+        if self.elevation.is_empty() || self.bin_width <= 0.0 {
+            return 1.0;
+        }
+        let bin = ((azimuth.rem_euclid(360.0) / self.bin_width) as usize).min(self.elevation.len() - 1);
+
+        if elevation < self.elevation[bin] {
+            self.transmittance[bin]
+        } else {
+            1.0
+        }
+    }
+
+    /// Checks that the mask has bins and one elevation and transmittance per bin
+    ///
+    pub fn validate(&self) -> Result<(), ProdError> {
+        if self.bin_width <= 0.0 || self.elevation.is_empty() {
+            return Err("horizon mask must have a positive bin width and at least one bin".into());
+        }
+        if self.transmittance.len() != self.elevation.len() {
+            return Err("horizon mask must have one transmittance per elevation bin".into());
+        }
+
+        Ok(())
+    }
+
+    /// Saves the mask to the cache directory
+    ///
+    /// # Arguments
+    ///
+    /// * 'cache_dir' - directory to store the mask in
+    pub fn save(&self, cache_dir: &str) -> Result<(), ProdError> {
+        let path = format!("{}{}", cache_dir, HORIZON_FILE);
+        let json = serde_json::to_string(self).map_err(|e| ProdError(e.to_string()))?;
+
+        fs::write(&path, json).map_err(|e| ProdError(format!("unable to write {}: {}", path, e)))
+    }
+
+    /// Loads the mask from the cache directory
+    ///
+    /// # Arguments
+    ///
+    /// * 'cache_dir' - directory holding the mask
+    pub fn load(cache_dir: &str) -> Result<Self, ProdError> {
+        let path = format!("{}{}", cache_dir, HORIZON_FILE);
+        let json = fs::read_to_string(&path).map_err(|e| ProdError(format!("unable to read {}: {}", path, e)))?;
+
+        let mask: Self = serde_json::from_str(&json).map_err(|e| ProdError(format!("invalid {}: {}", path, e)))?;
+        mask.validate().map_err(|e| ProdError(format!("invalid {}: {}", path, e.0)))?;
+
+        Ok(mask)
+    }
+}
+
+/// Shading from a horizon mask, typically learned from historical production
+///
+/// The mask is read from `horizon.json` in the cache directory and applies to both planes.
+///
+pub struct HorizonShading {
+    mask: Result<HorizonMask, ProdError>,
+}
+
+impl HorizonShading {
+    /// Returns a new instance of HorizonShading with the mask loaded from the cache directory,
+    /// a missing or invalid mask is reported when shading is calculated
+    ///
+    /// # Arguments
+    ///
+    /// * 'cache_dir' - directory holding the horizon mask
+    pub fn new(cache_dir: &str) -> Self {
+        Self { mask: HorizonMask::load(cache_dir) }
+    }
+}
+
+impl ShadingModel for HorizonShading {
+    fn shading(&self, _params: &Parameters, solar_positions: &SolarPositions) -> Result<Shading, ProdError> {
+        let mask = self.mask.as_ref().map_err(|e| ProdError(e.0.clone()))?;
+
+        Ok(mask_shading(mask, solar_positions))
+    }
+}
+
+/// Returns the shading from a horizon mask, the same for both planes
+///
+/// # Arguments
+///
+/// * 'mask' - horizon mask
+/// * 'solar_positions' - solar positions during the day
+pub fn mask_shading(mask: &HorizonMask, solar_positions: &SolarPositions) -> Shading {
+    let mut factors: [f64;1440] = [0.0; 1440];
+    let mut up = solar_positions.sunset;
+
+    for m in (solar_positions.sunrise..solar_positions.sunset).filter(|m| solar_positions.sun_up[*m]) {
+        factors[m] = mask.beam_factor(solar_positions.azimuth[m], solar_positions.elevation[m]);
+        if up == solar_positions.sunset && factors[m] >= 1.0 {
+            up = m;
+        }
+    }
+
+    Shading { east: factors, west: factors, up }
+}
@@ -0,0 +1,32 @@
+use crate::manager_production::pipeline::IamModel;
+use crate::models::Parameters;
+
+/// Schlick incidence angle modifier with flatness given by `iam_factor`
+///
+pub struct Schlick;
+
+impl IamModel for Schlick {
+    fn iam(&self, incidence: f64, params: &Parameters) -> f64 {
+        schlick_iam(incidence, params.iam_factor)
+    }
+}
+
+/// Plain cosine projection without any additional reflection losses
+///
+pub struct Cosine;
+
+impl IamModel for Cosine {
+    fn iam(&self, incidence: f64, _params: &Parameters) -> f64 {
+        incidence.clamp(0.0, 90.0).to_radians().cos()
+    }
+}
+
+/// The Schlick Incidence Angle Modifier algorithm
+///
+/// # Arguments
+///
+/// * 'theta_deg' - Sun-panel incidence angle
+/// * 'factor' - level of flatness, 1 gives cosine flatness, higher values give more flatness
+pub fn schlick_iam(theta_deg: f64, factor: f64) -> f64 {
+    1.0 - (1.0 - theta_deg.clamp(0.0, 90.0).to_radians().cos()).powf(factor)
+}
@@ -0,0 +1,270 @@
+pub mod errors;
+pub mod pipeline;
+pub mod benchmark;
+pub mod horizon;
+mod models;
+mod solar;
+mod solar_cache;
+mod mount;
+mod clear_sky;
+mod transposition;
+mod shading;
+mod self_shading;
+mod seasonal;
+mod scene;
+mod iam;
+mod thermal;
+mod sky;
+mod electrical;
+
+use std::ops::Add;
+use chrono::{DateTime, Datelike, Local, TimeDelta, TimeZone};
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::{SolarPositions, ThermalState};
+use crate::manager_production::pipeline::{solar_position_model, ModelChoice, Pipeline};
+use crate::manager_production::sky::{dew_point, sky_temperature};
+use crate::models::{DataItem, Moisture, MoistureKind, Parameters, Production};
+use crate::parallel::par_map;
+
+/// Number of panels on the east facing plane
+const PANELS_EAST: f64 = 12.0;
+
+/// Number of panels on the west facing plane
+const PANELS_WEST: f64 = 15.0;
+
+/// Returns a vector of production values per minute
+///
+/// The day is computed on a worker thread since the production model keeps many per minute
+/// arrays on the stack.
+///
+/// # Arguments
+///
+/// * 'params' - parameters to use in calculations
+/// * 'models' - model variant to use for each stage of the production pipeline
+/// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+pub fn get_day_production(params: Parameters, models: &ModelChoice, cache_dir: &str) -> Result<Production, ProdError> {
+    get_range_production(&[params], models, cache_dir)?
+        .pop()
+        .ok_or("no production for the day".into())
+}
+
+/// Returns production values per minute for several days, computed in parallel
+///
+/// The days are independent of each other and results are returned in the same order as the
+/// given parameters.
+///
+/// # Arguments
+///
+/// * 'params' - parameters to use in calculations, one per day
+/// * 'models' - model variant to use for each stage of the production pipeline
+/// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+pub fn get_range_production(params: &[Parameters], models: &ModelChoice, cache_dir: &str) -> Result<Vec<Production>, ProdError> {
+    let pipeline = Pipeline::new(models, cache_dir);
+
+    par_map(params, |p| day_power(p, day_start(p), &pipeline))
+        .into_iter()
+        .collect()
+}
+
+/// Returns production values per minute for several days like `get_range_production`, solar
+/// positions cached only for the days the given predicate accepts
+///
+/// Meant for perturbed panel orientations that would otherwise fill the cache directory with
+/// solar positions never asked for again.
+///
+/// # Arguments
+///
+/// * 'params' - parameters to use in calculations, one per day
+/// * 'models' - model variant to use for each stage of the production pipeline
+/// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+/// * 'cache' - whether to cache the solar positions for the parameters of a day
+pub fn get_range_production_cached_if<F>(params: &[Parameters], models: &ModelChoice, cache_dir: &str, cache: F) -> Result<Vec<Production>, ProdError>
+where
+    F: Fn(&Parameters) -> bool + Sync,
+{
+    let cached = Pipeline::new(models, cache_dir);
+    let uncached = Pipeline { solar_position: solar_position_model(models.solar_position), ..Pipeline::new(models, cache_dir) };
+
+    par_map(params, |p| day_power(p, day_start(p), if cache(p) { &cached } else { &uncached }))
+        .into_iter()
+        .collect()
+}
+
+/// Returns production values per minute for variants of parameters and models, computed in
+/// parallel
+///
+/// Each variant runs its own pipeline and results are returned in the same order as the given
+/// variants, a failing variant does not stop the others.
+///
+/// # Arguments
+///
+/// * 'variants' - parameters and model variant to use for each stage, one per variant
+/// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+pub fn get_variant_production(variants: &[(Parameters, ModelChoice)], cache_dir: &str) -> Vec<Result<Production, ProdError>> {
+    par_map(variants, |(p, models)| day_power(p, day_start(p), &Pipeline::new(models, cache_dir)))
+}
+
+/// Calculates one day estimated power per minute
+///
+/// # Arguments
+///
+/// * 'params' - struct of parameters
+/// * 'date_time' - date to calculate for
+/// * 'pipeline' - stage implementations to run
+fn day_power(params: &Parameters, date_time: DateTime<Local>, pipeline: &Pipeline) -> Result<Production, ProdError> {
+    let sp = pipeline.solar_position.solar_positions(date_time, params)?;
+
+    day_power_from_positions(params, date_time, pipeline, &sp)
+}
+
+/// Calculates one day estimated power per minute from already known solar positions
+///
+/// # Arguments
+///
+/// * 'params' - struct of parameters
+/// * 'date_time' - date to calculate for
+/// * 'pipeline' - stage implementations to run after the solar position stage
+/// * 'sp' - solar positions for the date
+fn day_power_from_positions(params: &Parameters, date_time: DateTime<Local>, pipeline: &Pipeline, sp: &SolarPositions) -> Result<Production, ProdError> {
+    let mut power: [f64;1440] = [0.0;1440];
+    let orientation = pipeline.mount.orientation(params, sp)?;
+    let sun_intensity_factor = pipeline.clear_sky.sun_intensity(sp);
+    let shading = pipeline.shading.shading(params, sp)?;
+    let (start_east, start_west) = thermal_start(params, date_time, pipeline)?;
+    let temperature_east = pipeline.thermal.plane_temperature(params, shading.up, &orientation.incidence_east, &sun_intensity_factor, start_east)?;
+    let temperature_west = pipeline.thermal.plane_temperature(params, shading.up, &orientation.incidence_west, &sun_intensity_factor, start_west)?;
+
+    // Loop through the day with a one-minute incrementation
+    for minute_of_day in (sp.sunrise..sp.sunset).filter(|m| sp.sun_up[*m]) {
+        // Split sun intensity, reduced by the atmospheric effect, into components on the planes
+        let irr = pipeline.transposition.plane_irradiance(sun_intensity_factor[minute_of_day], sp.zenith[minute_of_day], orientation.slope[minute_of_day]);
+
+        // Effective irradiance on each side given shading and sun incidence angles
+        let eff_e = irr.beam * shading.east[minute_of_day] * pipeline.iam.iam(orientation.incidence_east[minute_of_day], params) + irr.diffuse;
+        let eff_w = irr.beam * shading.west[minute_of_day] * pipeline.iam.iam(orientation.incidence_west[minute_of_day], params) + irr.diffuse;
+
+        // Calculate total panel power where each side is reduced by its own panel temperature
+        let dc_e = pipeline.dc.dc_power(params, PANELS_EAST, eff_e, temperature_east.module[minute_of_day]);
+        let dc_w = pipeline.dc.dc_power(params, PANELS_WEST, eff_w, temperature_west.module[minute_of_day]);
+
+        // Record the estimated power at the given point in time
+        power[minute_of_day] = pipeline.ac.ac_power(dc_e + dc_w);
+    }
+
+    let dew_point = dew_point(params);
+    let mut moisture = moisture_periods(date_time, "east", &temperature_east.module, &dew_point);
+    moisture.append(&mut moisture_periods(date_time, "west", &temperature_west.module, &dew_point));
+
+    Ok(Production {
+        power: prepare_result(date_time, &power),
+        incidence_east: prepare_result(date_time, &orientation.incidence_east),
+        incidence_west: prepare_result(date_time, &orientation.incidence_west),
+        surface_slope: prepare_result(date_time, &orientation.slope),
+        ambient_temperature: prepare_result(date_time, &params.temp),
+        module_temperature_east: prepare_result(date_time, &temperature_east.module),
+        module_temperature_west: prepare_result(date_time, &temperature_west.module),
+        roof_temperature_east: prepare_result(date_time, &temperature_east.roof),
+        roof_temperature_west: prepare_result(date_time, &temperature_west.roof),
+        sky_temperature: prepare_result(date_time, &sky_temperature(params)),
+        dew_point: prepare_result(date_time, &dew_point),
+        moisture,
+        sun_intensity_factor: prepare_result(date_time, &sun_intensity_factor),
+        sun_azimuth: prepare_result(date_time, &sp.azimuth),
+        sun_elevation: prepare_result(date_time, &sp.elevation),
+    })
+}
+
+/// Returns the thermal state at midnight for each plane, simulated over the previous day from its
+/// ambient temperature, or none if the previous day's temperature is unknown
+///
+/// # Arguments
+///
+/// * 'params' - struct of parameters
+/// * 'date_time' - date to calculate the start state for
+/// * 'pipeline' - stage implementations to run
+fn thermal_start(params: &Parameters, date_time: DateTime<Local>, pipeline: &Pipeline) -> Result<(Option<ThermalState>, Option<ThermalState>), ProdError> {
+    let Some(temp_previous) = params.temp_previous else {
+        return Ok((None, None));
+    };
+
+    let previous = date_time.date_naive().pred_opt().ok_or("no previous day")?;
+    let previous_params = Parameters {
+        year: previous.year(),
+        month: previous.month(),
+        day: previous.day(),
+        temp: temp_previous,
+        temp_previous: None,
+        humidity: None,
+        cloud_cover: None,
+        clouds: None,
+        ..params.clone()
+    };
+    let previous_start = day_start(&previous_params);
+
+    let sp = pipeline.solar_position.solar_positions(previous_start, &previous_params)?;
+    let orientation = pipeline.mount.orientation(&previous_params, &sp)?;
+    let sun_intensity_factor = pipeline.clear_sky.sun_intensity(&sp);
+    let shading = pipeline.shading.shading(&previous_params, &sp)?;
+    let east = pipeline.thermal.plane_temperature(&previous_params, shading.up, &orientation.incidence_east, &sun_intensity_factor, None)?;
+    let west = pipeline.thermal.plane_temperature(&previous_params, shading.up, &orientation.incidence_west, &sun_intensity_factor, None)?;
+
+    Ok((Some(east.end_state()), Some(west.end_state())))
+}
+
+/// Returns the periods when a panel plane is below the dew point, as frost when it is also below
+/// freezing and otherwise as dew
+///
+/// # Arguments
+///
+/// * 'date_time' - date time truncated to day
+/// * 'plane' - name of the panel plane
+/// * 'module' - module temperature per minute
+/// * 'dew_point' - dew point per minute
+fn moisture_periods(date_time: DateTime<Local>, plane: &'static str, module: &[f64;1440], dew_point: &[f64;1440]) -> Vec<Moisture> {
+    let kind = |i: usize| match module[i] < dew_point[i] {
+        true if module[i] <= 0.0 => Some(MoistureKind::Frost),
+        true => Some(MoistureKind::Dew),
+        false => None,
+    };
+
+    let mut result: Vec<Moisture> = Vec::new();
+    let mut start: usize = 0;
+    for i in 1..=1440 {
+        if i == 1440 || kind(i) != kind(start) {
+            if let Some(k) = kind(start) {
+                result.push(Moisture {
+                    plane,
+                    kind: k,
+                    from: date_time.add(TimeDelta::minutes(start as i64)),
+                    to: date_time.add(TimeDelta::minutes(i as i64 - 1)),
+                });
+            }
+            start = i;
+        }
+    }
+
+    result
+}
+
+/// Returns the start of the day given in the parameters
+///
+/// # Arguments
+///
+/// * 'params' - parameters carrying the date
+fn day_start(params: &Parameters) -> DateTime<Local> {
+    Local::now()
+        .timezone()
+        .with_ymd_and_hms(params.year, params.month, params.day, 0, 0, 0)
+        .unwrap()
+}
+
+/// Prepares a result vector of data items
+///
+/// # Arguments
+///
+/// * 'date_time' - date time truncated to day
+fn prepare_result(date_time: DateTime<Local>, default: &[f64]) -> Vec<DataItem> {
+    (0..1440)
+        .map(|i| DataItem{x: date_time.add(TimeDelta::minutes(i)), y: default[i as usize]})
+        .collect::<Vec<DataItem>>()
+}
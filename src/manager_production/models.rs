@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+/// Sun angles per minute of the day in degrees, as seen from the site and from each plane
+///
+#[serde_as]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SolarPositions {
+    #[serde_as(as = "[_; 1440]")]
+    pub incidence_east: [f64;1440],
+    #[serde_as(as = "[_; 1440]")]
+    pub incidence_west: [f64;1440],
+    #[serde_as(as = "[_; 1440]")]
+    pub azimuth: [f64;1440],
+    #[serde_as(as = "[_; 1440]")]
+    pub elevation: [f64;1440],
+    #[serde_as(as = "[_; 1440]")]
+    pub zenith: [f64;1440],
+    /// Whether the sun is above the horizon, the sun may set and rise again within a day
+    #[serde_as(as = "[_; 1440]")]
+    pub sun_up: [bool;1440],
+    /// First minute with the sun up
+    pub sunrise: usize,
+    /// Minute after the last minute with the sun up
+    pub sunset: usize,
+}
+
+/// Orientation of the planes per minute, the slope they share and the sun incidence on each
+///
+pub struct PlaneOrientation {
+    pub slope: [f64;1440],
+    pub incidence_east: [f64;1440],
+    pub incidence_west: [f64;1440],
+}
+
+/// Irradiance components on a plane, relative to the intensity external to the earth's atmosphere
+///
+pub struct PlaneIrradiance {
+    pub beam: f64,
+    pub diffuse: f64,
+}
+
+/// Beam shading factors per minute for each plane, 1.0 meaning no shading
+///
+pub struct Shading {
+    pub east: [f64;1440],
+    pub west: [f64;1440],
+    pub up: usize,
+}
+
+/// Temperatures per minute for one plane, the module node and the roof node below it
+///
+pub struct PlaneTemperature {
+    pub module: [f64;1440],
+    pub roof: [f64;1440],
+}
+
+impl PlaneTemperature {
+    /// Returns the state at the last minute of the day, to start the next day from
+    ///
+    pub fn end_state(&self) -> ThermalState {
+        ThermalState { module: self.module[1439], roof: self.roof[1439] }
+    }
+}
+
+/// Temperatures of the thermal nodes at one point in time
+///
+#[derive(Clone, Copy)]
+pub struct ThermalState {
+    pub module: f64,
+    pub roof: f64,
+}
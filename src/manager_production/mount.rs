@@ -0,0 +1,151 @@
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::{PlaneOrientation, SolarPositions};
+use crate::manager_production::pipeline::MountModel;
+use crate::models::Parameters;
+
+type Vec3 = [f64;3];
+
+/// Planes fixed on the roof with the slope and azimuth in the parameters
+///
+pub struct Fixed;
+
+impl MountModel for Fixed {
+    fn orientation(&self, params: &Parameters, solar_positions: &SolarPositions) -> Result<PlaneOrientation, ProdError> {
+        Ok(PlaneOrientation {
+            slope: [params.panel_slope; 1440],
+            incidence_east: solar_positions.incidence_east,
+            incidence_west: solar_positions.incidence_west,
+        })
+    }
+}
+
+/// Single-axis tracker turning the panels around one axis towards the sun
+///
+/// The axis points towards `axis_azimuth`, eastward from north, and is lowered by `axis_tilt`
+/// at that end, so a north-south axis tilted towards the equator has azimuth 180. At rotation 0
+/// the panels face straight up from the axis, positive rotation turns them to the right of the
+/// axis direction, i.e. west for a southward axis, and the rotation is limited to `max_angle`.
+/// With backtracking the rotation is reduced whenever the row in front would shade the next one,
+/// given the ground coverage ratio `gcr` (panel width over row pitch).
+///
+/// Both planes are carried by the tracker and share its orientation.
+///
+pub struct SingleAxis {
+    pub axis_azimuth: f64,
+    pub axis_tilt: f64,
+    pub max_angle: f64,
+    pub gcr: f64,
+    pub backtrack: bool,
+}
+
+impl MountModel for SingleAxis {
+    fn orientation(&self, _params: &Parameters, solar_positions: &SolarPositions) -> Result<PlaneOrientation, ProdError> {
+        if self.backtrack && !(self.gcr > 0.0 && self.gcr <= 1.0) {
+            return Err("ground coverage ratio must be within (0,1] for backtracking".into());
+        }
+
+        let (sin_a, cos_a) = self.axis_azimuth.to_radians().sin_cos();
+        let (sin_t, cos_t) = self.axis_tilt.to_radians().sin_cos();
+        let axis: Vec3 = [sin_a * cos_t, cos_a * cos_t, -sin_t];
+        let up: Vec3 = [sin_a * sin_t, cos_a * sin_t, cos_t];
+        let side = cross(&axis, &up);
+
+        let stow = normal_slope(&up);
+        let mut orientation = PlaneOrientation { slope: [stow; 1440], incidence_east: [90.0; 1440], incidence_west: [90.0; 1440] };
+
+        for m in (solar_positions.sunrise..solar_positions.sunset).filter(|m| solar_positions.sun_up[*m]) {
+            let sun = sun_vector(solar_positions.azimuth[m], solar_positions.zenith[m]);
+
+            // Ideal rotation points the normal at the sun projected on the plane across the axis
+            let ideal = dot(&sun, &side).atan2(dot(&sun, &up));
+            let rotation = if self.backtrack {
+                backtrack(ideal, self.gcr)
+            } else {
+                ideal
+            };
+            let rotation = rotation.clamp(-self.max_angle.to_radians(), self.max_angle.to_radians());
+
+            let (sin_r, cos_r) = rotation.sin_cos();
+            let normal = [0, 1, 2].map(|k| up[k] * cos_r + side[k] * sin_r);
+            let incidence = dot(&sun, &normal).clamp(-1.0, 1.0).acos().to_degrees().min(90.0);
+
+            orientation.slope[m] = normal_slope(&normal);
+            orientation.incidence_east[m] = incidence;
+            orientation.incidence_west[m] = incidence;
+        }
+
+        Ok(orientation)
+    }
+}
+
+/// Dual-axis tracker pointing the panels straight at the sun, limited to `max_slope` from the
+/// horizontal after which it keeps facing the sun azimuth at that slope
+///
+/// Both planes are carried by the tracker and share its orientation.
+///
+pub struct DualAxis {
+    pub max_slope: f64,
+}
+
+impl MountModel for DualAxis {
+    fn orientation(&self, _params: &Parameters, solar_positions: &SolarPositions) -> Result<PlaneOrientation, ProdError> {
+        let mut orientation = PlaneOrientation { slope: [0.0; 1440], incidence_east: [90.0; 1440], incidence_west: [90.0; 1440] };
+
+        for m in (solar_positions.sunrise..solar_positions.sunset).filter(|m| solar_positions.sun_up[*m]) {
+            let zenith = solar_positions.zenith[m];
+            let slope = zenith.min(self.max_slope.max(0.0));
+
+            orientation.slope[m] = slope;
+            orientation.incidence_east[m] = zenith - slope;
+            orientation.incidence_west[m] = zenith - slope;
+        }
+
+        Ok(orientation)
+    }
+}
+
+/// Returns the rotation reduced so that rows just avoid shading each other, following the
+/// backtracking of Anderson and Mikofski (2020) for a level ground
+///
+/// # Arguments
+///
+/// * 'ideal' - rotation pointing at the sun in radians
+/// * 'gcr' - ground coverage ratio
+fn backtrack(ideal: f64, gcr: f64) -> f64 {
+    let shade = ideal.cos().abs() / gcr;
+    if shade < 1.0 {
+        ideal - ideal.signum() * shade.acos()
+    } else {
+        ideal
+    }
+}
+
+/// Returns the unit vector towards the sun, x east, y north and z up
+///
+/// # Arguments
+///
+/// * 'azimuth' - sun azimuth, eastward from north, in degrees
+/// * 'zenith' - sun zenith angle in degrees
+fn sun_vector(azimuth: f64, zenith: f64) -> Vec3 {
+    let (sin_a, cos_a) = azimuth.to_radians().sin_cos();
+    let (sin_z, cos_z) = zenith.to_radians().sin_cos();
+
+    [sin_a * sin_z, cos_a * sin_z, cos_z]
+}
+
+/// Returns the slope from the horizontal in degrees of a plane with the given unit normal
+///
+/// # Arguments
+///
+/// * 'normal' - unit normal of the plane
+fn normal_slope(normal: &Vec3) -> f64 {
+    normal[2].clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+fn dot(a: &Vec3, b: &Vec3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: &Vec3, b: &Vec3) -> Vec3 {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
@@ -0,0 +1,269 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use crate::manager_production::clear_sky::{AirMass, Meinel};
+use crate::manager_production::electrical::{Inverter, LinearTemperature, Passthrough};
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::horizon::HorizonShading;
+use crate::manager_production::iam::{Cosine, Schlick};
+use crate::manager_production::models::{PlaneIrradiance, PlaneOrientation, PlaneTemperature, Shading, SolarPositions, ThermalState};
+use crate::manager_production::mount::{DualAxis, Fixed, SingleAxis};
+use crate::manager_production::scene::SceneShading;
+use crate::manager_production::seasonal::SeasonalShading;
+use crate::manager_production::self_shading::{GableGeometry, GableShading};
+use crate::manager_production::shading::{HorizonRamp, Unshaded};
+use crate::manager_production::solar::{Psa, Spa, SpaInterpolated};
+use crate::manager_production::solar_cache::CachedSolarPositions;
+use crate::manager_production::thermal::{RoofRc, TwoNode};
+use crate::manager_production::transposition::{Beam, Isotropic};
+use crate::models::Parameters;
+
+/// Solar position stage, sun angles per minute for the site and each plane
+///
+pub trait SolarPositionModel: Send + Sync {
+    fn solar_positions(&self, date_time: DateTime<Local>, params: &Parameters) -> Result<SolarPositions, ProdError>;
+
+    /// Identifies the model and its settings, used to tell cached results apart
+    fn id(&self) -> String;
+}
+
+/// Mount stage, slope of the planes and sun incidence on each plane per minute, fixed or tracking
+/// the sun
+///
+pub trait MountModel: Send + Sync {
+    fn orientation(&self, params: &Parameters, solar_positions: &SolarPositions) -> Result<PlaneOrientation, ProdError>;
+}
+
+/// Clear sky stage, sun intensity per minute relative to the intensity external to the atmosphere
+///
+pub trait ClearSkyModel: Send + Sync {
+    fn sun_intensity(&self, solar_positions: &SolarPositions) -> [f64;1440];
+}
+
+/// Transposition stage, splits sun intensity into components on a plane with the given slope
+///
+pub trait TranspositionModel: Send + Sync {
+    fn plane_irradiance(&self, sun_intensity: f64, zenith: f64, slope: f64) -> PlaneIrradiance;
+}
+
+/// Shading stage, beam shading factors per minute for each plane
+///
+pub trait ShadingModel: Send + Sync {
+    fn shading(&self, params: &Parameters, solar_positions: &SolarPositions) -> Result<Shading, ProdError>;
+}
+
+/// Incidence angle modifier stage, share of beam irradiance accepted by the panel at a given incidence
+///
+pub trait IamModel: Send + Sync {
+    fn iam(&self, incidence: f64, params: &Parameters) -> f64;
+}
+
+/// Thermal stage, module and roof temperature per minute for one plane, starting from the given
+/// state at midnight or from a model specific guess if there is none
+///
+pub trait ThermalModel: Send + Sync {
+    fn plane_temperature(&self, params: &Parameters, up: usize, incidence: &[f64;1440], sun_intensity: &[f64;1440], initial: Option<ThermalState>) -> Result<PlaneTemperature, ProdError>;
+}
+
+/// DC stage, power in W from a number of panels given effective irradiance and panel temperature
+///
+pub trait DcModel: Send + Sync {
+    fn dc_power(&self, params: &Parameters, panels: f64, irradiance: f64, panel_temperature: f64) -> f64;
+}
+
+/// AC stage, inverter output in kW given total DC power in W
+///
+pub trait AcModel: Send + Sync {
+    fn ac_power(&self, dc_power: f64) -> f64;
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SolarPositionChoice {
+    #[default]
+    Spa,
+    SpaInterpolated { step: usize },
+    Psa,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MountChoice {
+    #[default]
+    Fixed,
+    SingleAxis { axis_azimuth: f64, axis_tilt: f64, max_angle: f64, gcr: f64, backtrack: bool },
+    DualAxis { max_slope: f64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClearSkyChoice {
+    #[default]
+    AirMass,
+    Meinel,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranspositionChoice {
+    #[default]
+    Beam,
+    Isotropic,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShadingChoice {
+    #[default]
+    HorizonRamp,
+    None,
+    Scene,
+    Horizon,
+    Seasonal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfShadingChoice {
+    #[default]
+    None,
+    Gable { ridge_height: f64, rail_height: f64, gap_east: f64, gap_west: f64, width_east: f64, width_west: f64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum IamChoice {
+    #[default]
+    Schlick,
+    Cosine,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThermalChoice {
+    #[default]
+    RoofRc,
+    TwoNode { tau_module: f64, k_roof: f64, coupling: f64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DcChoice {
+    #[default]
+    LinearTemperature,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AcChoice {
+    #[default]
+    Passthrough,
+    Inverter { max_power: f64, efficiency: f64 },
+}
+
+/// Model variant to use for each stage of the production pipeline, stages left out of the
+/// `[model]` section of the config file use their default, e.g.
+///
+/// ```toml
+/// [model]
+/// shading = "scene"
+/// thermal = { two_node = { tau_module = 0.1, k_roof = 20.0, coupling = 0.5 } }
+/// ac = { inverter = { max_power = 10.0, efficiency = 0.97 } }
+/// ```
+///
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct ModelChoice {
+    pub solar_position: SolarPositionChoice,
+    pub mount: MountChoice,
+    pub clear_sky: ClearSkyChoice,
+    pub transposition: TranspositionChoice,
+    pub shading: ShadingChoice,
+    pub self_shading: SelfShadingChoice,
+    pub iam: IamChoice,
+    pub thermal: ThermalChoice,
+    pub dc: DcChoice,
+    pub ac: AcChoice,
+}
+
+/// The production pipeline, one implementation per stage
+///
+pub struct Pipeline {
+    pub solar_position: Box<dyn SolarPositionModel>,
+    pub mount: Box<dyn MountModel>,
+    pub clear_sky: Box<dyn ClearSkyModel>,
+    pub transposition: Box<dyn TranspositionModel>,
+    pub shading: Box<dyn ShadingModel>,
+    pub iam: Box<dyn IamModel>,
+    pub thermal: Box<dyn ThermalModel>,
+    pub dc: Box<dyn DcModel>,
+    pub ac: Box<dyn AcModel>,
+}
+
+impl Pipeline {
+    /// Returns a new pipeline with stage implementations as given by the model choice
+    ///
+    /// # Arguments
+    ///
+    /// * 'choice' - model variant to use for each stage
+    /// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+    pub fn new(choice: &ModelChoice, cache_dir: &str) -> Self {
+        let solar_position = Box::new(CachedSolarPositions::new(solar_position_model(choice.solar_position), cache_dir));
+        let mount: Box<dyn MountModel> = match choice.mount {
+            MountChoice::Fixed => Box::new(Fixed),
+            MountChoice::SingleAxis { axis_azimuth, axis_tilt, max_angle, gcr, backtrack } => Box::new(SingleAxis { axis_azimuth, axis_tilt, max_angle, gcr, backtrack }),
+            MountChoice::DualAxis { max_slope } => Box::new(DualAxis { max_slope }),
+        };
+        let clear_sky: Box<dyn ClearSkyModel> = match choice.clear_sky {
+            ClearSkyChoice::AirMass => Box::new(AirMass),
+            ClearSkyChoice::Meinel => Box::new(Meinel),
+        };
+        let transposition: Box<dyn TranspositionModel> = match choice.transposition {
+            TranspositionChoice::Beam => Box::new(Beam),
+            TranspositionChoice::Isotropic => Box::new(Isotropic),
+        };
+        let shading: Box<dyn ShadingModel> = match choice.shading {
+            ShadingChoice::HorizonRamp => Box::new(HorizonRamp),
+            ShadingChoice::None => Box::new(Unshaded),
+            ShadingChoice::Scene => Box::new(SceneShading::new(cache_dir)),
+            ShadingChoice::Horizon => Box::new(HorizonShading::new(cache_dir)),
+            ShadingChoice::Seasonal => Box::new(SeasonalShading::new(cache_dir)),
+        };
+        let shading: Box<dyn ShadingModel> = match choice.self_shading {
+            SelfShadingChoice::None => shading,
+            SelfShadingChoice::Gable { ridge_height, rail_height, gap_east, gap_west, width_east, width_west } => Box::new(GableShading {
+                inner: shading,
+                geometry: GableGeometry { ridge_height, rail_height, gap_east, gap_west, width_east, width_west },
+            }),
+        };
+        let iam: Box<dyn IamModel> = match choice.iam {
+            IamChoice::Schlick => Box::new(Schlick),
+            IamChoice::Cosine => Box::new(Cosine),
+        };
+        let thermal: Box<dyn ThermalModel> = match choice.thermal {
+            ThermalChoice::RoofRc => Box::new(RoofRc),
+            ThermalChoice::TwoNode { tau_module, k_roof, coupling } => Box::new(TwoNode { tau_module, k_roof, coupling }),
+        };
+        let dc: Box<dyn DcModel> = match choice.dc {
+            DcChoice::LinearTemperature => Box::new(LinearTemperature),
+        };
+        let ac: Box<dyn AcModel> = match choice.ac {
+            AcChoice::Passthrough => Box::new(Passthrough),
+            AcChoice::Inverter { max_power, efficiency } => Box::new(Inverter { max_power, efficiency }),
+        };
+
+        Self { solar_position, mount, clear_sky, transposition, shading, iam, thermal, dc, ac }
+    }
+}
+
+/// Returns an uncached solar position model
+///
+/// # Arguments
+///
+/// * 'choice' - solar position model variant
+pub fn solar_position_model(choice: SolarPositionChoice) -> Box<dyn SolarPositionModel> {
+    match choice {
+        SolarPositionChoice::Spa => Box::new(Spa),
+        SolarPositionChoice::SpaInterpolated { step } => Box::new(SpaInterpolated { step }),
+        SolarPositionChoice::Psa => Box::new(Psa),
+    }
+}
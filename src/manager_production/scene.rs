@@ -0,0 +1,291 @@
+use std::fs;
+use serde::Deserialize;
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::{Shading, SolarPositions};
+use crate::manager_production::pipeline::ShadingModel;
+use crate::models::Parameters;
+
+/// Name of the scene description file in the cache directory
+const SCENE_FILE: &str = "scene.json";
+
+/// Sample points along each edge of an array when not given in the scene
+const DEFAULT_SAMPLES: usize = 8;
+
+/// Rays starting this close to an obstacle surface are not considered blocked by it [m]
+const EPSILON: f64 = 1e-6;
+
+type Vec3 = [f64;3];
+
+/// Obstacle in the local coordinate system, x east, y north and z up in metres
+///
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Obstacle {
+    /// Box standing on its bottom face, e.g. a building, dormer or chimney, rotated counterclockwise
+    /// seen from above by `rotation` degrees around its vertical centre line
+    Box { center: Vec3, size: Vec3, #[serde(default)] rotation: f64 },
+    /// Vertical cylinder standing on its base, e.g. a tree trunk or chimney pipe
+    Cylinder { base: Vec3, radius: f64, height: f64 },
+    /// Vertical cone standing on its base, e.g. a tree crown
+    Cone { base: Vec3, radius: f64, height: f64 },
+}
+
+/// Panel array rectangle spanned by two edges from a corner, belonging to one of the planes
+///
+#[derive(Deserialize, Clone)]
+pub struct Array {
+    /// Plane the array belongs to, "east" or "west"
+    pub plane: String,
+    pub corner: Vec3,
+    pub edge_u: Vec3,
+    pub edge_v: Vec3,
+    /// Sample points along each edge
+    pub samples: Option<usize>,
+}
+
+/// Obstacles and panel arrays around the site
+///
+#[derive(Deserialize, Clone)]
+pub struct Scene {
+    pub obstacles: Vec<Obstacle>,
+    pub arrays: Vec<Array>,
+}
+
+/// Shading from obstacles in a 3D scene, ray-cast from sample points on each array towards the
+/// sun every minute the sun is up
+///
+/// The scene is read from `scene.json` in the cache directory. The beam factor of a plane is the
+/// share of its sample points, weighted by array area, with a free line of sight to the sun.
+///
+pub struct SceneShading {
+    scene: Result<Scene, String>,
+}
+
+impl SceneShading {
+    /// Returns a new instance of SceneShading with the scene loaded from the cache directory,
+    /// a missing or invalid scene, e.g. with an array without area, is reported when shading is
+    /// calculated
+    ///
+    /// # Arguments
+    ///
+    /// * 'cache_dir' - directory holding the scene description
+    pub fn new(cache_dir: &str) -> Self {
+        let path = format!("{}{}", cache_dir, SCENE_FILE);
+        let scene = fs::read_to_string(&path)
+            .map_err(|e| format!("unable to read {}: {}", path, e))
+            .and_then(|json| serde_json::from_str::<Scene>(&json).map_err(|e| format!("invalid {}: {}", path, e)))
+            .and_then(|scene| match scene.arrays.iter().position(|a| !area(a).is_normal()) {
+                Some(i) => Err(format!("invalid {}: array {} has no area", path, i)),
+                None => Ok(scene),
+            });
+
+        Self { scene }
+    }
+}
+
+impl ShadingModel for SceneShading {
+    fn shading(&self, _params: &Parameters, solar_positions: &SolarPositions) -> Result<Shading, ProdError> {
+        let scene = self.scene.as_ref().map_err(|e| ProdError(e.clone()))?;
+
+        let east = sample_points(scene, "east");
+        let west = sample_points(scene, "west");
+        if east.is_empty() || west.is_empty() {
+            return Err("scene must have arrays on both the east and the west plane".into());
+        }
+
+        let mut result = Shading { east: [0.0; 1440], west: [0.0; 1440], up: solar_positions.sunset };
+
+        for m in (solar_positions.sunrise..solar_positions.sunset).filter(|m| solar_positions.sun_up[*m]) {
+            let sun = sun_direction(solar_positions.azimuth[m], solar_positions.elevation[m]);
+
+            result.east[m] = lit_share(&scene.obstacles, &east, &sun);
+            result.west[m] = lit_share(&scene.obstacles, &west, &sun);
+
+            if result.up == solar_positions.sunset && result.east[m].max(result.west[m]) > 0.5 {
+                result.up = m;
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Returns sample points on all arrays of a plane, each with its share of the plane's area
+///
+/// # Arguments
+///
+/// * 'scene' - scene with the arrays
+/// * 'plane' - plane to sample
+fn sample_points(scene: &Scene, plane: &str) -> Vec<(Vec3, f64)> {
+    let arrays = scene.arrays.iter().filter(|a| a.plane == plane).collect::<Vec<&Array>>();
+    let total_area = arrays.iter().map(|a| area(a)).sum::<f64>();
+
+    let mut result: Vec<(Vec3, f64)> = Vec::new();
+    for a in arrays {
+        let n = a.samples.unwrap_or(DEFAULT_SAMPLES).max(1);
+        let weight = area(a) / total_area / (n * n) as f64;
+
+        for i in 0..n {
+            for j in 0..n {
+                let (fu, fv) = ((i as f64 + 0.5) / n as f64, (j as f64 + 0.5) / n as f64);
+                let point = [0, 1, 2].map(|k| a.corner[k] + a.edge_u[k] * fu + a.edge_v[k] * fv);
+                result.push((point, weight));
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns the area of an array
+///
+/// # Arguments
+///
+/// * 'array' - the array
+fn area(array: &Array) -> f64 {
+    let (u, v) = (array.edge_u, array.edge_v);
+    let cross = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+
+    cross.iter().map(|c| c * c).sum::<f64>().sqrt()
+}
+
+/// Returns the unit vector towards the sun
+///
+/// # Arguments
+///
+/// * 'azimuth' - sun azimuth, eastward from north, in degrees
+/// * 'elevation' - sun elevation in degrees
+fn sun_direction(azimuth: f64, elevation: f64) -> Vec3 {
+    let (sin_a, cos_a) = azimuth.to_radians().sin_cos();
+    let (sin_e, cos_e) = elevation.to_radians().sin_cos();
+
+    [sin_a * cos_e, cos_a * cos_e, sin_e]
+}
+
+/// Returns the weighted share of sample points with a free line of sight to the sun
+///
+/// # Arguments
+///
+/// * 'obstacles' - obstacles in the scene
+/// * 'points' - sample points and their weights
+/// * 'sun' - unit vector towards the sun
+fn lit_share(obstacles: &[Obstacle], points: &[(Vec3, f64)], sun: &Vec3) -> f64 {
+    points.iter()
+        .filter(|(p, _)| !obstacles.iter().any(|o| blocks(o, p, sun)))
+        .map(|(_, w)| w)
+        .sum()
+}
+
+/// Returns true if the obstacle is hit by the ray from the origin in the given direction
+///
+/// # Arguments
+///
+/// * 'obstacle' - obstacle to test
+/// * 'origin' - start of the ray
+/// * 'dir' - direction of the ray
+fn blocks(obstacle: &Obstacle, origin: &Vec3, dir: &Vec3) -> bool {
+    match obstacle {
+        Obstacle::Box { center, size, rotation } => {
+            // Move the ray into the box frame where the box is axis aligned
+            let (sin_r, cos_r) = (-rotation).to_radians().sin_cos();
+            let rotate = |x: f64, y: f64| (x * cos_r - y * sin_r, x * sin_r + y * cos_r);
+            let (ox, oy) = rotate(origin[0] - center[0], origin[1] - center[1]);
+            let (dx, dy) = rotate(dir[0], dir[1]);
+
+            let min = [-size[0] / 2.0, -size[1] / 2.0, 0.0];
+            let max = [size[0] / 2.0, size[1] / 2.0, size[2]];
+            ray_box([ox, oy, origin[2] - center[2]], [dx, dy, dir[2]], min, max)
+        },
+        Obstacle::Cylinder { base, radius, height } => ray_cone(origin, dir, base, *radius, *radius, *height),
+        Obstacle::Cone { base, radius, height } => ray_cone(origin, dir, base, *radius, 0.0, *height),
+    }
+}
+
+/// Returns true if a ray hits an axis aligned box, using the slab method
+///
+/// # Arguments
+///
+/// * 'origin' - start of the ray
+/// * 'dir' - direction of the ray
+/// * 'min' - lowest corner of the box
+/// * 'max' - highest corner of the box
+fn ray_box(origin: Vec3, dir: Vec3, min: Vec3, max: Vec3) -> bool {
+    let mut t_near = EPSILON;
+    let mut t_far = f64::INFINITY;
+
+    for k in 0..3 {
+        if dir[k].abs() < 1e-12 {
+            if origin[k] < min[k] || origin[k] > max[k] {
+                return false;
+            }
+        } else {
+            let t0 = (min[k] - origin[k]) / dir[k];
+            let t1 = (max[k] - origin[k]) / dir[k];
+            t_near = t_near.max(t0.min(t1));
+            t_far = t_far.min(t0.max(t1));
+            if t_near > t_far {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Returns true if a ray hits a vertical truncated cone, a cylinder when both radii are equal
+///
+/// # Arguments
+///
+/// * 'origin' - start of the ray
+/// * 'dir' - direction of the ray
+/// * 'base' - centre of the bottom face
+/// * 'r_bottom' - radius at the bottom
+/// * 'r_top' - radius at the top
+/// * 'height' - height from bottom to top
+fn ray_cone(origin: &Vec3, dir: &Vec3, base: &Vec3, r_bottom: f64, r_top: f64, height: f64) -> bool {
+    if height <= 0.0 {
+        return false;
+    }
+    let q = [origin[0] - base[0], origin[1] - base[1], origin[2] - base[2]];
+
+    // Radius varies linearly with height, r(z) = r_bottom + slope * z
+    let slope = (r_top - r_bottom) / height;
+    let radius_at = |z: f64| r_bottom + slope * z;
+    let inside_height = |t: f64| {
+        let z = q[2] + t * dir[2];
+        (0.0..=height).contains(&z)
+    };
+
+    // Mantle, (qx + t dx)² + (qy + t dy)² = (r_bottom + slope (qz + t dz))²
+    let r0 = radius_at(q[2]);
+    let a = dir[0] * dir[0] + dir[1] * dir[1] - slope * slope * dir[2] * dir[2];
+    let b = 2.0 * (q[0] * dir[0] + q[1] * dir[1] - r0 * slope * dir[2]);
+    let c = q[0] * q[0] + q[1] * q[1] - r0 * r0;
+
+    let roots: Vec<f64> = if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 { Vec::new() } else { vec![-c / b] }
+    } else {
+        let disc = b * b - 4.0 * a * c;
+        if disc < 0.0 {
+            Vec::new()
+        } else {
+            vec![(-b - disc.sqrt()) / (2.0 * a), (-b + disc.sqrt()) / (2.0 * a)]
+        }
+    };
+    if roots.iter().any(|t| *t > EPSILON && inside_height(*t) && radius_at(q[2] + t * dir[2]) >= 0.0) {
+        return true;
+    }
+
+    // Bottom and top faces
+    if dir[2].abs() > 1e-12 {
+        for (z, r) in [(0.0, r_bottom), (height, r_top)] {
+            let t = (z - q[2]) / dir[2];
+            let (x, y) = (q[0] + t * dir[0], q[1] + t * dir[1]);
+            if t > EPSILON && x * x + y * y <= r * r {
+                return true;
+            }
+        }
+    }
+
+    false
+}
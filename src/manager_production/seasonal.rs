@@ -0,0 +1,203 @@
+use std::fs;
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::horizon::{mask_shading, HorizonMask};
+use crate::manager_production::models::{Shading, SolarPositions};
+use crate::manager_production::pipeline::ShadingModel;
+use crate::manager_production::shading::HorizonRamp;
+use crate::models::Parameters;
+
+/// Name of the shading schedule file in the cache directory
+const SCHEDULE_FILE: &str = "shading_schedule.json";
+
+/// Shading during one season, either ramp thresholds replacing those in the parameters or a
+/// horizon mask
+///
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum SeasonShading {
+    Ramp { start_azm: f64, start_elv: f64, stop_azm: f64, stop_elv: f64 },
+    Mask(HorizonMask),
+}
+
+/// Month and day of month, the same date every year
+///
+#[derive(Deserialize, Clone, Copy)]
+pub struct MonthDay {
+    pub month: u32,
+    pub day: u32,
+}
+
+/// When each shading applies during the year
+///
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ShadingSchedule {
+    /// One shading per month, January first
+    Monthly { months: Vec<SeasonShading> },
+    /// Leaf-off shading from `bare` to `leaf_out`, leaf-on shading from `full_leaf` to `leaf_fall`
+    /// and shading interpolated linearly in between while the trees leaf out and shed
+    Leaf {
+        leaf_off: SeasonShading,
+        leaf_on: SeasonShading,
+        leaf_out: MonthDay,
+        full_leaf: MonthDay,
+        leaf_fall: MonthDay,
+        bare: MonthDay,
+    },
+}
+
+/// Shading that varies over the year, for deciduous trees that block much less in winter
+///
+/// The schedule is read from `shading_schedule.json` in the cache directory and the shading for
+/// the simulated date is picked or interpolated from it. Ramp seasons apply to both planes just as
+/// the `HorizonRamp` model does, mask seasons as the `Horizon` model does.
+///
+pub struct SeasonalShading {
+    schedule: Result<ShadingSchedule, ProdError>,
+}
+
+impl SeasonalShading {
+    /// Returns a new instance of SeasonalShading with the schedule loaded from the cache directory,
+    /// a missing or invalid schedule is reported when shading is calculated
+    ///
+    /// # Arguments
+    ///
+    /// * 'cache_dir' - directory holding the shading schedule
+    pub fn new(cache_dir: &str) -> Self {
+        let path = format!("{}{}", cache_dir, SCHEDULE_FILE);
+        let schedule = fs::read_to_string(&path)
+            .map_err(|e| ProdError(format!("unable to read {}: {}", path, e)))
+            .and_then(|json| serde_json::from_str::<ShadingSchedule>(&json).map_err(|e| ProdError(format!("invalid {}: {}", path, e))))
+            .and_then(|schedule| match validate_masks(&schedule) {
+                Ok(()) => Ok(schedule),
+                Err(e) => Err(ProdError(format!("invalid {}: {}", path, e.0))),
+            });
+
+        Self { schedule }
+    }
+}
+
+impl ShadingModel for SeasonalShading {
+    fn shading(&self, params: &Parameters, solar_positions: &SolarPositions) -> Result<Shading, ProdError> {
+        let schedule = self.schedule.as_ref().map_err(|e| ProdError(e.0.clone()))?;
+        let season = season_shading(schedule, params)?;
+
+        match season {
+            SeasonShading::Ramp { start_azm, start_elv, stop_azm, stop_elv } => {
+                let ramp_params = Parameters { start_azm, start_elv, stop_azm, stop_elv, ..params.clone() };
+                HorizonRamp.shading(&ramp_params, solar_positions)
+            },
+            SeasonShading::Mask(mask) => Ok(mask_shading(&mask, solar_positions)),
+        }
+    }
+}
+
+/// Checks every horizon mask in a schedule
+///
+/// # Arguments
+///
+/// * 'schedule' - shading schedule
+fn validate_masks(schedule: &ShadingSchedule) -> Result<(), ProdError> {
+    let seasons = match schedule {
+        ShadingSchedule::Monthly { months } => months.iter().collect::<Vec<&SeasonShading>>(),
+        ShadingSchedule::Leaf { leaf_off, leaf_on, .. } => vec![leaf_off, leaf_on],
+    };
+
+    seasons.iter()
+        .filter_map(|s| match s {
+            SeasonShading::Mask(mask) => Some(mask),
+            SeasonShading::Ramp { .. } => None,
+        })
+        .try_for_each(|mask| mask.validate())
+}
+
+/// Returns the shading that applies on the date in the parameters
+///
+/// # Arguments
+///
+/// * 'schedule' - shading schedule
+/// * 'params' - parameters holding the date
+fn season_shading(schedule: &ShadingSchedule, params: &Parameters) -> Result<SeasonShading, ProdError> {
+    let date = NaiveDate::from_ymd_opt(params.year, params.month, params.day)
+        .ok_or(ProdError(format!("invalid date {}-{}-{}", params.year, params.month, params.day)))?;
+
+    match schedule {
+        ShadingSchedule::Monthly { months } => {
+            if months.len() != 12 {
+                return Err("a monthly shading schedule must have 12 months".into());
+            }
+            Ok(months[date.month0() as usize].clone())
+        },
+        ShadingSchedule::Leaf { leaf_off, leaf_on, leaf_out, full_leaf, leaf_fall, bare } => {
+            let out = day_of_year(params.year, leaf_out)?;
+            let full = day_of_year(params.year, full_leaf)?;
+            let fall = day_of_year(params.year, leaf_fall)?;
+            let bare = day_of_year(params.year, bare)?;
+            if !(out <= full && full <= fall && fall <= bare) {
+                return Err("leaf dates must be in the order leaf out, full leaf, leaf fall and bare".into());
+            }
+
+            let day = date.ordinal();
+            let leaf_share = if day < out || day >= bare {
+                0.0
+            } else if day < full {
+                (day - out) as f64 / (full - out) as f64
+            } else if day < fall {
+                1.0
+            } else {
+                1.0 - (day - fall) as f64 / (bare - fall) as f64
+            };
+
+            interpolate(leaf_off, leaf_on, leaf_share)
+        },
+    }
+}
+
+/// Returns the day of the year, starting at 1, for a month and day in the given year
+///
+/// # Arguments
+///
+/// * 'year' - the year
+/// * 'month_day' - month and day of month
+fn day_of_year(year: i32, month_day: &MonthDay) -> Result<u32, ProdError> {
+    NaiveDate::from_ymd_opt(year, month_day.month, month_day.day)
+        .map(|d| d.ordinal())
+        .ok_or(ProdError(format!("invalid leaf date {}-{}", month_day.month, month_day.day)))
+}
+
+/// Returns shading linearly interpolated between two seasons of the same kind, masks must also
+/// have the same bins
+///
+/// # Arguments
+///
+/// * 'from' - shading at share 0
+/// * 'to' - shading at share 1
+/// * 'share' - how far from 'from' towards 'to', between 0 and 1
+fn interpolate(from: &SeasonShading, to: &SeasonShading, share: f64) -> Result<SeasonShading, ProdError> {
+    let lerp = |a: f64, b: f64| a + (b - a) * share;
+
+    match (from, to) {
+        (
+            SeasonShading::Ramp { start_azm: a0, start_elv: e0, stop_azm: a1, stop_elv: e1 },
+            SeasonShading::Ramp { start_azm: b0, start_elv: f0, stop_azm: b1, stop_elv: f1 },
+        ) => Ok(SeasonShading::Ramp {
+            start_azm: lerp(*a0, *b0),
+            start_elv: lerp(*e0, *f0),
+            stop_azm: lerp(*a1, *b1),
+            stop_elv: lerp(*e1, *f1),
+        }),
+        (SeasonShading::Mask(a), SeasonShading::Mask(b)) => {
+            if a.bin_width != b.bin_width || a.elevation.len() != b.elevation.len() || a.transmittance.len() != b.transmittance.len() {
+                return Err("leaf-off and leaf-on masks must have the same bins".into());
+            }
+            Ok(SeasonShading::Mask(HorizonMask {
+                bin_width: a.bin_width,
+                elevation: a.elevation.iter().zip(&b.elevation).map(|(x, y)| lerp(*x, *y)).collect(),
+                transmittance: a.transmittance.iter().zip(&b.transmittance).map(|(x, y)| lerp(*x, *y)).collect(),
+            }))
+        },
+        _ => Err("leaf-off and leaf-on shading must both be ramps or both be masks".into()),
+    }
+}
@@ -0,0 +1,103 @@
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::{Shading, SolarPositions};
+use crate::manager_production::pipeline::ShadingModel;
+use crate::models::Parameters;
+
+/// Geometry of the module fields on an east/west gable roof, all lengths in metres
+///
+/// Gaps and widths are measured along the roof surface, from the ridge and down the slope.
+///
+#[derive(Clone, Copy)]
+pub struct GableGeometry {
+    /// Height of the ridge cap above where the two roof planes meet
+    pub ridge_height: f64,
+    /// Height of the module surface above the roof surface
+    pub rail_height: f64,
+    /// Distance from the ridge to the upper edge of the east module field
+    pub gap_east: f64,
+    /// Distance from the ridge to the upper edge of the west module field
+    pub gap_west: f64,
+    /// Width of the east module field
+    pub width_east: f64,
+    /// Width of the west module field
+    pub width_west: f64,
+}
+
+/// Self-shading between the two planes of a gable roof on top of another shading model
+///
+/// With the sun on the east side of the ridge the ridge casts a shadow down the west plane and
+/// vice versa. The roof planes themselves never shade the opposite plane while it faces the sun,
+/// it is what sticks up at the ridge that does, so the shadow grows from the ridge as the sun's
+/// profile angle approaches the plane slope. The beam factor of a plane is reduced by the shaded
+/// share of its module field.
+///
+pub struct GableShading {
+    pub inner: Box<dyn ShadingModel>,
+    pub geometry: GableGeometry,
+}
+
+impl ShadingModel for GableShading {
+    fn shading(&self, params: &Parameters, solar_positions: &SolarPositions) -> Result<Shading, ProdError> {
+        let mut shading = self.inner.shading(params, solar_positions)?;
+        let g = &self.geometry;
+        let slope = params.panel_slope.to_radians();
+        let east_facing = 180.0 + params.panel_east_azm;
+
+        for m in (solar_positions.sunrise..solar_positions.sunset).filter(|m| solar_positions.sun_up[*m]) {
+            let across = (solar_positions.azimuth[m] - east_facing).to_radians().cos();
+            let tan_profile = profile_tangent(solar_positions.elevation[m], across.abs());
+
+            if across > 0.0 {
+                shading.west[m] *= 1.0 - shaded_share(tan_profile, slope, g.ridge_height, g.rail_height, g.gap_west, g.width_west);
+            } else {
+                shading.east[m] *= 1.0 - shaded_share(tan_profile, slope, g.ridge_height, g.rail_height, g.gap_east, g.width_east);
+            }
+        }
+
+        Ok(shading)
+    }
+}
+
+/// Returns the tangent of the sun's profile angle, its elevation projected on the vertical plane
+/// across the ridge
+///
+/// # Arguments
+///
+/// * 'elevation' - sun elevation in degrees
+/// * 'across' - cosine of the angle between the sun azimuth and the direction across the ridge
+fn profile_tangent(elevation: f64, across: f64) -> f64 {
+    if across < 1e-6 {
+        f64::INFINITY
+    } else {
+        elevation.to_radians().tan() / across
+    }
+}
+
+/// Returns the share of a module field in the shadow of the ridge, 0 when the plane faces away
+/// from the sun since it then gets no beam irradiance anyway
+///
+/// In the vertical plane across the ridge, with the ridge at the origin and the shaded plane
+/// going down towards positive x, the module surface is at s along the slope
+///   (s cos β + h sin β, -s sin β + h cos β)
+/// and the ray grazing the ridge top (0, r) is (t cos α, r - t sin α). Solving for s gives the
+/// lower edge of the shadow.
+///
+/// # Arguments
+///
+/// * 'tan_profile' - tangent of the sun's profile angle
+/// * 'slope' - plane slope in radians
+/// * 'ridge_height' - height of the ridge cap
+/// * 'rail_height' - height of the module surface above the roof surface
+/// * 'gap' - distance from the ridge to the upper edge of the module field
+/// * 'width' - width of the module field
+fn shaded_share(tan_profile: f64, slope: f64, ridge_height: f64, rail_height: f64, gap: f64, width: f64) -> f64 {
+    let (sin_s, cos_s) = slope.sin_cos();
+    if tan_profile.is_infinite() || tan_profile <= sin_s / cos_s || width <= 0.0 {
+        return 0.0;
+    }
+
+    let shadow = (rail_height * cos_s - ridge_height + tan_profile * rail_height * sin_s)
+        / (sin_s - tan_profile * cos_s);
+
+    ((shadow - gap) / width).clamp(0.0, 1.0)
+}
@@ -0,0 +1,161 @@
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::{Shading, SolarPositions};
+use crate::manager_production::pipeline::ShadingModel;
+use crate::models::Parameters;
+
+/// Shading from nearby obstacles as exponential ramps after sunrise and before sunset,
+/// where the ramps end/start where the sun passes the start/stop azimuth and elevation thresholds
+///
+pub struct HorizonRamp;
+
+impl ShadingModel for HorizonRamp {
+    fn shading(&self, params: &Parameters, solar_positions: &SolarPositions) -> Result<Shading, ProdError> {
+        let (up, down) = full_sun_minute(params, solar_positions);
+        let mut factors: [f64;1440] = [0.0; 1440];
+
+        factors.iter_mut()
+            .enumerate()
+            .take(solar_positions.sunset)
+            .skip(solar_positions.sunrise)
+            .filter(|(minute_of_day, _)| solar_positions.sun_up[*minute_of_day])
+            .for_each(|(minute_of_day, factor)| {
+                let shadow_up = exp_increase(minute_of_day, solar_positions.sunrise, up, 10);
+                let shadow_down = exp_decrease(minute_of_day, down, solar_positions.sunset, 4);
+
+                *factor = shadow_up * shadow_down;
+            });
+
+        Ok(Shading { east: factors, west: factors, up })
+    }
+}
+
+/// No shading at all
+///
+pub struct Unshaded;
+
+impl ShadingModel for Unshaded {
+    fn shading(&self, _params: &Parameters, solar_positions: &SolarPositions) -> Result<Shading, ProdError> {
+        Ok(Shading { east: [1.0; 1440], west: [1.0; 1440], up: solar_positions.sunrise })
+    }
+}
+
+/// Finds the points in time (minute) where the sun is free from nearby obstacles
+///
+/// The morning threshold is searched for from the sun's lowest point before solar noon, once it has
+/// passed north, and the evening threshold only after the morning one and after solar noon, so that
+/// a low sun in the north on a midnight sun day isn't taken for the evening.
+/// If the sun never clears the obstacles in the morning it is taken to be free at sunset, and
+/// if it never reaches the obstacles in the evening it is taken to be free until sunset.
+///
+/// # Arguments
+///
+/// * 'params' - input parameters
+/// * 'solar_positions' - solar positions during the day
+fn full_sun_minute(params: &Parameters, solar_positions: &SolarPositions) -> (usize, usize) {
+    let minutes: Vec<usize> = (solar_positions.sunrise..solar_positions.sunset)
+        .filter(|m| solar_positions.sun_up[*m])
+        .collect();
+    let by_elevation = |a: &usize, b: &usize| solar_positions.elevation[*a].total_cmp(&solar_positions.elevation[*b]);
+
+    let noon = minutes.iter().copied().max_by(by_elevation).unwrap_or(solar_positions.sunset);
+    let lowest = minutes.iter().copied().take_while(|m| *m <= noon).min_by(by_elevation).unwrap_or(solar_positions.sunrise);
+
+    let up = minutes.iter().copied()
+        .skip_while(|m| *m < lowest || solar_positions.azimuth[*m] > 180.0)
+        .find(|m| solar_positions.elevation[*m] > params.start_elv || solar_positions.azimuth[*m] > params.start_azm)
+        .unwrap_or(solar_positions.sunset);
+
+    let down = minutes.iter().copied()
+        .skip_while(|m| *m < up.max(noon))
+        .find(|m| solar_positions.azimuth[*m] > 180.0 && (solar_positions.elevation[*m] < params.stop_elv || solar_positions.azimuth[*m] > params.stop_azm))
+        .unwrap_or(solar_positions.sunset);
+
+    (up, down)
+}
+
+/// Calculates an exponential increase for v between v0 and vn
+/// The output is an exponential factor between 0 and 1
+///
+/// # Arguments
+///
+/// * 'v' - the input value
+/// * 'v0' - the starting point for when v influences the output
+/// * 'vn' - the end point for when v gives an output of 1 and no longer influences output
+/// * 'exp' - exponent that determines the exponential shape, a step at vn if vn is not after v0
+fn exp_increase(v: usize, v0: usize, vn: usize, exp: i32) -> f64 {
+    if vn <= v0 {
+        return if v >= vn { 1.0 } else { 0.0 };
+    }
+    let denominator = (vn - v0) as f64;
+    let enumerator = (v as f64 - v0 as f64).clamp(0.0, denominator);
+
+    (enumerator / denominator).powi(exp)
+}
+
+/// Calculates an exponential decrease for v between v0 and vn
+/// The output is an exponential decrease factor between 1 and 0
+///
+/// # Arguments
+///
+/// * 'v' - the input value
+/// * 'v0' - the starting point for when v influences the output
+/// * 'vn' - the end point for when v gives an output of 1 and no longer influences output
+/// * 'exp' - exponent that determines the exponential shape, a step at v0 if vn is not after v0
+fn exp_decrease(v: usize, v0: usize, vn: usize, exp: i32) -> f64 {
+    if vn <= v0 {
+        return if v < v0 { 1.0 } else { 0.0 };
+    }
+    let denominator = (vn - v0) as f64;
+    let enumerator = (vn as f64 - v as f64).clamp(0.0, denominator);
+
+    (enumerator / denominator).powi(exp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, Offset, TimeZone};
+    use crate::manager_production::clear_sky::AirMass;
+    use crate::manager_production::pipeline::{ClearSkyModel, SolarPositionModel};
+    use crate::manager_production::solar::Spa;
+
+    #[test]
+    fn midnight_sun_keeps_the_day() {
+        let date_time = Local.with_ymd_and_hms(2025, 6, 21, 0, 0, 0).single().unwrap();
+        // Put solar midnight at 01:00 local time whatever the time zone, so the day starts with a low sun in the north
+        let offset_hours = date_time.offset().fix().local_minus_utc() as f64 / 3600.0;
+        let params = Parameters {
+            year: 2025,
+            month: 6,
+            day: 21,
+            lat: 68.35,
+            long: 15.0 * (offset_hours - 1.0),
+            temp: [15.0; 1440],
+            temp_previous: None,
+            humidity: None,
+            cloud_cover: None,
+            clouds: None,
+            panel_power: 430.0,
+            panel_slope: 40.5,
+            panel_east_azm: -82.5,
+            panel_temp_red: 0.35,
+            tau: 0.5,
+            tau_down: 0.8,
+            k_gain: 30.0,
+            iam_factor: 2.0,
+            start_azm: 100.0,
+            start_elv: 10.0,
+            stop_azm: 280.0,
+            stop_elv: 10.0,
+        };
+        let solar_positions = Spa.solar_positions(date_time, &params).unwrap();
+        assert!(solar_positions.sun_up.iter().all(|up| *up));
+
+        let sun_intensity = AirMass.sun_intensity(&solar_positions);
+        let shading = HorizonRamp.shading(&params, &solar_positions).unwrap();
+        let unshaded: f64 = sun_intensity.iter().sum();
+        let shaded: f64 = sun_intensity.iter().zip(shading.east.iter()).map(|(s, f)| s * f).sum();
+
+        assert!(shaded > 0.8 * unshaded, "shaded {} unshaded {}", shaded, unshaded);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::models::Parameters;
+
+/// Relative humidity [%] used when no humidity is known for the day
+const DEFAULT_HUMIDITY: f64 = 70.0;
+
+/// Cloud cover [0,1] used when no cloud cover is known for the day
+const DEFAULT_CLOUD_COVER: f64 = 0.5;
+
+/// Share of a surface's heat exchange that is long wave radiation to the sky rather than
+/// convection to the ambient air, about 5 W/m²K out of 15 W/m²K for a surface in light wind
+const RADIATIVE_SHARE: f64 = 0.35;
+
+/// Magnus formula coefficients over water, valid roughly between -45 °C and 60 °C
+const MAGNUS_B: f64 = 17.62;
+const MAGNUS_C: f64 = 243.12;
+
+/// Returns the ambient temperature per minute as experienced by a surface facing the sky, that
+/// is the ambient temperature pulled towards the sky temperature by the radiative share
+///
+/// # Arguments
+///
+/// * 'params' - parameters carrying ambient temperature and optionally humidity and cloud cover
+pub fn effective_air_temperature(params: &Parameters) -> [f64;1440] {
+    let t_sky = sky_temperature(params);
+
+    let mut result: [f64;1440] = [0.0; 1440];
+    (0..1440)
+        .for_each(|i| {
+            result[i] = params.temp[i] - RADIATIVE_SHARE * (params.temp[i] - t_sky[i]);
+        });
+
+    result
+}
+
+/// Returns the effective sky temperature per minute, the temperature of a black body radiating
+/// as much long wave radiation as the sky
+///
+/// The clear sky emissivity follows Berdahl & Martin from the dew point, and clouds close the
+/// gap towards a black body sky following a simplified Kasten & Czeplak correction.
+///
+/// # Arguments
+///
+/// * 'params' - parameters carrying ambient temperature and optionally humidity and cloud cover
+pub fn sky_temperature(params: &Parameters) -> [f64;1440] {
+    let dew_point = dew_point(params);
+
+    let mut result: [f64;1440] = [0.0; 1440];
+    (0..1440)
+        .for_each(|i| {
+            let cloud = params.cloud_cover.map_or(DEFAULT_CLOUD_COVER, |c| c[i].clamp(0.0, 1.0));
+            let tdp = dew_point[i] / 100.0;
+            let e_clear = (0.711 + 0.56 * tdp + 0.73 * tdp * tdp).clamp(0.0, 1.0);
+            let e_sky = e_clear + (1.0 - e_clear) * 0.8 * cloud;
+
+            result[i] = e_sky.powf(0.25) * (params.temp[i] + 273.15) - 273.15;
+        });
+
+    result
+}
+
+/// Returns the dew point per minute using the Magnus formula
+///
+/// # Arguments
+///
+/// * 'params' - parameters carrying ambient temperature and optionally humidity
+pub fn dew_point(params: &Parameters) -> [f64;1440] {
+    let mut result: [f64;1440] = [0.0; 1440];
+    (0..1440)
+        .for_each(|i| {
+            let rh = params.humidity.map_or(DEFAULT_HUMIDITY, |h| h[i].clamp(1.0, 100.0));
+            let gamma = (rh / 100.0).ln() + MAGNUS_B * params.temp[i] / (MAGNUS_C + params.temp[i]);
+
+            result[i] = MAGNUS_C * gamma / (MAGNUS_B - gamma);
+        });
+
+    result
+}
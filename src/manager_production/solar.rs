@@ -0,0 +1,378 @@
+use std::f64::consts::PI;
+use std::ops::Add;
+use chrono::{DateTime, Datelike, DurationRound, Local, TimeDelta, Timelike, Utc};
+use spa_sra::spa::{Function, Input, SpaData};
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::SolarPositions;
+use crate::manager_production::pipeline::SolarPositionModel;
+use crate::models::Parameters;
+
+/// Site elevation [m]
+const ELEVATION: f64 = 61.0;
+
+/// Annual average local pressure [mbar]
+const PRESSURE: f64 = 1013.0;
+
+/// Annual average local temperature [°C]
+const TEMPERATURE: f64 = 10.0;
+
+/// Sun elevation at sunrise and sunset given the sun radius and atmospheric refraction [degrees]
+const SUN_HORIZON: f64 = -(0.26667 + 0.5667);
+
+/// Solar positions from the NREL Solar Position Algorithm, evaluated every minute
+///
+pub struct Spa;
+
+impl SolarPositionModel for Spa {
+    fn solar_positions(&self, date_time: DateTime<Local>, params: &Parameters) -> Result<SolarPositions, ProdError> {
+        spa_positions(date_time, params, 1)
+    }
+
+    fn id(&self) -> String {
+        "spa".to_string()
+    }
+}
+
+/// Solar positions from the NREL Solar Position Algorithm, evaluated every `step` minutes and
+/// linearly interpolated in between
+///
+pub struct SpaInterpolated {
+    pub step: usize,
+}
+
+impl SolarPositionModel for SpaInterpolated {
+    fn solar_positions(&self, date_time: DateTime<Local>, params: &Parameters) -> Result<SolarPositions, ProdError> {
+        spa_positions(date_time, params, self.step.max(1))
+    }
+
+    fn id(&self) -> String {
+        format!("spa{}", self.step.max(1))
+    }
+}
+
+/// Solar positions from the PSA algorithm (Blanco-Muriel et al. 2001), a fast approximation
+/// with an error well below 0.01 degrees within 1999-2015, plus atmospheric refraction as in SPA
+///
+pub struct Psa;
+
+impl SolarPositionModel for Psa {
+    fn solar_positions(&self, date_time: DateTime<Local>, params: &Parameters) -> Result<SolarPositions, ProdError> {
+        Ok(psa_positions(date_time, params))
+    }
+
+    fn id(&self) -> String {
+        "psa".to_string()
+    }
+}
+
+/// Sun angles at one point in time in degrees, before clamping
+///
+#[derive(Clone, Copy)]
+struct SunSample {
+    incidence_east: f64,
+    incidence_west: f64,
+    zenith: f64,
+    azimuth: f64,
+    elevation: f64,
+}
+
+impl SunSample {
+    /// Returns a sample linearly interpolated between two samples, azimuth taking the shortest way
+    ///
+    /// # Arguments
+    ///
+    /// * 'other' - sample to interpolate towards
+    /// * 'f' - fraction of the way to the other sample
+    fn lerp(&self, other: &SunSample, f: f64) -> SunSample {
+        let d_azimuth = (other.azimuth - self.azimuth + 540.0).rem_euclid(360.0) - 180.0;
+
+        SunSample {
+            incidence_east: self.incidence_east + (other.incidence_east - self.incidence_east) * f,
+            incidence_west: self.incidence_west + (other.incidence_west - self.incidence_west) * f,
+            zenith: self.zenith + (other.zenith - self.zenith) * f,
+            azimuth: (self.azimuth + d_azimuth * f).rem_euclid(360.0),
+            elevation: self.elevation + (other.elevation - self.elevation) * f,
+        }
+    }
+}
+
+/// Returns sun incidence, zenith, azimuth and elevation angles per minute in degrees for the given date
+/// using SPA every `step` minutes and linear interpolation in between.
+///
+/// On ordinary days SPA is evaluated between sunrise and sunset. On days without both a sunrise and a
+/// later sunset the same day, that is polar day, polar night or a sun still up after midnight, the
+/// whole day is evaluated and the sun is up where it is above the horizon.
+///
+/// # Arguments
+///
+/// * 'date_time' - DateTime object carrying the date of interest
+/// * 'params' - various input parameters
+/// * 'step' - minutes between SPA evaluations, 1 evaluates every minute
+fn spa_positions(date_time: DateTime<Local>, params: &Parameters, step: usize) -> Result<SolarPositions, ProdError> {
+    let mut input = Input::from_date_time(date_time);
+    input.latitude = params.lat;
+    input.longitude = params.long;
+    input.pressure = PRESSURE;
+    input.temperature = TEMPERATURE;
+    input.elevation = ELEVATION;
+    input.slope = params.panel_slope;
+    input.azm_rotation = 0.0;
+    input.function = Function::SpaZaRts;
+
+    let mut spa = SpaData::new(input);
+    spa.spa_calculate()?;
+
+    let rise_and_set = if spa.spa_za_rts.sunrise >= 0.0 && spa.spa_za_rts.sunset >= 0.0 {
+        let sunrise = spa.get_sunrise().duration_round(TimeDelta::minutes(1)).unwrap();
+        let sunset = spa.get_sunset().duration_round(TimeDelta::minutes(1)).unwrap();
+        (sunrise < sunset).then_some((sunrise, sunset))
+    } else {
+        None
+    };
+    let (from, to) = rise_and_set.unwrap_or((date_time, date_time.add(TimeDelta::days(1))));
+
+    spa.input.function = Function::SpaZaInc;
+
+    let mut samples: Vec<(usize, SunSample)> = Vec::new();
+    let mut time_of_interest = from;
+    let last = to.add(TimeDelta::minutes(-1));
+
+    while time_of_interest < to {
+        samples.push((minute_of_day(time_of_interest), spa_sample(&mut spa, time_of_interest, params)?));
+
+        if time_of_interest < last && time_of_interest.add(TimeDelta::minutes(step as i64)) > last {
+            time_of_interest = last;
+        } else {
+            time_of_interest = time_of_interest.add(TimeDelta::minutes(step as i64));
+        }
+    }
+
+    // Between sunrise and sunset the sun is up by definition, otherwise by its elevation
+    let mut sp = empty_positions();
+    let mut record = |toi: usize, sample: &SunSample| {
+        if rise_and_set.is_some() || sample.elevation > SUN_HORIZON {
+            record_sample(&mut sp, toi, sample);
+        }
+    };
+
+    if let Some(&(toi, sample)) = samples.last() {
+        record(toi, &sample);
+    }
+    for pair in samples.windows(2) {
+        let (t0, s0) = pair[0];
+        let (t1, s1) = pair[1];
+        for toi in t0..t1 {
+            record(toi, &s0.lerp(&s1, (toi - t0) as f64 / (t1 - t0) as f64));
+        }
+    }
+    set_sunrise_sunset(&mut sp);
+
+    Ok(sp)
+}
+
+/// Returns SPA sun angles for one point in time and both planes
+///
+/// # Arguments
+///
+/// * 'spa' - SPA data set up for incidence calculations
+/// * 'time_of_interest' - point in time
+/// * 'params' - various input parameters
+fn spa_sample(spa: &mut SpaData<Local>, time_of_interest: DateTime<Local>, params: &Parameters) -> Result<SunSample, ProdError> {
+    spa.input.date_time(time_of_interest);
+
+    spa.input.azm_rotation = params.panel_east_azm;
+    spa.spa_calculate()?;
+    let incidence_east = spa.spa_za_inc.incidence;
+
+    spa.input.azm_rotation = 180.0 + params.panel_east_azm;
+    spa.spa_calculate()?;
+
+    Ok(SunSample {
+        incidence_east,
+        incidence_west: spa.spa_za_inc.incidence,
+        zenith: spa.spa_za.zenith,
+        azimuth: spa.spa_za.azimuth,
+        elevation: spa.spa_za.e,
+    })
+}
+
+/// Returns sun incidence, zenith, azimuth and elevation angles per minute in degrees for the given date
+/// using the PSA algorithm for every minute of the day.
+///
+/// # Arguments
+///
+/// * 'date_time' - DateTime object carrying the date of interest
+/// * 'params' - various input parameters
+fn psa_positions(date_time: DateTime<Local>, params: &Parameters) -> SolarPositions {
+    let mut samples: Vec<(usize, SunSample)> = Vec::new();
+
+    for m in 0..1440 {
+        let time_of_interest = date_time.add(TimeDelta::minutes(m));
+        if time_of_interest.day() != date_time.day() {
+            break;
+        }
+
+        let (zenith, azimuth) = psa(time_of_interest.with_timezone(&Utc), params.lat, params.long);
+        let e0 = 90.0 - zenith;
+        let elevation = e0 + refraction_correction(e0);
+        let zenith = 90.0 - elevation;
+
+        samples.push((minute_of_day(time_of_interest), SunSample {
+            incidence_east: surface_incidence(zenith, azimuth, params.panel_east_azm, params.panel_slope),
+            incidence_west: surface_incidence(zenith, azimuth, 180.0 + params.panel_east_azm, params.panel_slope),
+            zenith,
+            azimuth,
+            elevation,
+        }));
+    }
+
+    let mut sp = empty_positions();
+    samples.iter()
+        .filter(|(_, s)| s.elevation > SUN_HORIZON)
+        .for_each(|(toi, sample)| record_sample(&mut sp, *toi, sample));
+    set_sunrise_sunset(&mut sp);
+
+    sp
+}
+
+/// Returns topocentric zenith (without refraction) and azimuth (eastward from north) in degrees
+/// according to the PSA algorithm
+///
+/// # Arguments
+///
+/// * 'utc' - point in time
+/// * 'lat' - latitude of site in degrees
+/// * 'long' - longitude of site in degrees
+fn psa(utc: DateTime<Utc>, lat: f64, long: f64) -> (f64, f64) {
+    const EARTH_MEAN_RADIUS: f64 = 6371.01;
+    const ASTRONOMICAL_UNIT: f64 = 149597890.0;
+    let rad = PI / 180.0;
+
+    let decimal_hours = utc.hour() as f64 + (utc.minute() as f64 + utc.second() as f64 / 60.0) / 60.0;
+
+    // Julian day and elapsed days since J2000.0
+    let (year, month, day) = (utc.year() as i64, utc.month() as i64, utc.day() as i64);
+    let aux1 = (month - 14) / 12;
+    let aux2 = (1461 * (year + 4800 + aux1)) / 4 + (367 * (month - 2 - 12 * aux1)) / 12
+        - (3 * ((year + 4900 + aux1) / 100)) / 4 + day - 32075;
+    let julian_date = aux2 as f64 - 0.5 + decimal_hours / 24.0;
+    let n = julian_date - 2451545.0;
+
+    // Ecliptic coordinates
+    let omega = 2.1429 - 0.0010394594 * n;
+    let mean_longitude = 4.8950630 + 0.017202791698 * n;
+    let mean_anomaly = 6.2400600 + 0.0172019699 * n;
+    let ecliptic_longitude = mean_longitude + 0.03341607 * mean_anomaly.sin() + 0.00034894 * (2.0 * mean_anomaly).sin()
+        - 0.0001134 - 0.0000203 * omega.sin();
+    let ecliptic_obliquity = 0.4090928 - 6.2140e-9 * n + 0.0000396 * omega.cos();
+
+    // Celestial coordinates
+    let sin_ecliptic_longitude = ecliptic_longitude.sin();
+    let right_ascension = (ecliptic_obliquity.cos() * sin_ecliptic_longitude)
+        .atan2(ecliptic_longitude.cos())
+        .rem_euclid(2.0 * PI);
+    let declination = (ecliptic_obliquity.sin() * sin_ecliptic_longitude).asin();
+
+    // Local coordinates
+    let greenwich_mean_sidereal_time = 6.6974243242 + 0.0657098283 * n + decimal_hours;
+    let local_mean_sidereal_time = (greenwich_mean_sidereal_time * 15.0 + long) * rad;
+    let hour_angle = local_mean_sidereal_time - right_ascension;
+    let (sin_lat, cos_lat) = (lat * rad).sin_cos();
+    let cos_hour_angle = hour_angle.cos();
+
+    let zenith = (cos_lat * cos_hour_angle * declination.cos() + declination.sin() * sin_lat).acos();
+    let azimuth = (-hour_angle.sin())
+        .atan2(declination.tan() * cos_lat - sin_lat * cos_hour_angle)
+        .rem_euclid(2.0 * PI);
+
+    // Parallax correction
+    let zenith = zenith + (EARTH_MEAN_RADIUS / ASTRONOMICAL_UNIT) * zenith.sin();
+
+    (zenith / rad, azimuth / rad)
+}
+
+/// Returns the atmospheric refraction correction in degrees for a sun elevation, as in SPA
+///
+/// # Arguments
+///
+/// * 'e0' - topocentric elevation angle without refraction in degrees
+fn refraction_correction(e0: f64) -> f64 {
+    if e0 >= SUN_HORIZON {
+        (PRESSURE / 1010.0) * (283.0 / (273.0 + TEMPERATURE)) * 1.02 / (60.0 * (e0 + 10.3 / (e0 + 5.11)).to_radians().tan())
+    } else {
+        0.0
+    }
+}
+
+/// Returns the sun incidence angle on a surface in degrees
+///
+/// # Arguments
+///
+/// * 'zenith' - topocentric zenith angle in degrees
+/// * 'azimuth' - topocentric azimuth angle, eastward from north, in degrees
+/// * 'azm_rotation' - surface azimuth rotation, measured from south, negative east, in degrees
+/// * 'slope' - surface slope from the horizontal plane in degrees
+pub fn surface_incidence(zenith: f64, azimuth: f64, azm_rotation: f64, slope: f64) -> f64 {
+    let (sin_z, cos_z) = zenith.to_radians().sin_cos();
+    let (sin_s, cos_s) = slope.to_radians().sin_cos();
+
+    (cos_z * cos_s + sin_s * sin_z * (azimuth - 180.0 - azm_rotation).to_radians().cos())
+        .clamp(-1.0, 1.0)
+        .acos()
+        .to_degrees()
+}
+
+/// Returns solar positions with no sun during the day
+///
+fn empty_positions() -> SolarPositions {
+    SolarPositions {
+        incidence_east: [90.0; 1440],
+        incidence_west: [90.0; 1440],
+        azimuth: [0.0; 1440],
+        elevation: [0.0; 1440],
+        zenith: [90.0; 1440],
+        sun_up: [false; 1440],
+        sunrise: 0,
+        sunset: 0,
+    }
+}
+
+/// Sets sunrise and sunset to the first minute the sun is up and the minute after the last
+/// minute the sun is up, both 0 when the sun is not up at all
+///
+/// # Arguments
+///
+/// * 'sp' - solar positions with recorded sun samples
+fn set_sunrise_sunset(sp: &mut SolarPositions) {
+    sp.sunrise = sp.sun_up.iter().position(|up| *up).unwrap_or(0);
+    sp.sunset = sp.sun_up.iter().rposition(|up| *up).map_or(0, |m| m + 1);
+}
+
+/// Records a clamped sun sample in the solar positions and marks the sun as up
+///
+/// # Arguments
+///
+/// * 'sp' - solar positions to record in
+/// * 'toi' - minute of day
+/// * 'sample' - sun angles to record
+fn record_sample(sp: &mut SolarPositions, toi: usize, sample: &SunSample) {
+    if toi >= 1440 {
+        return;
+    }
+
+    sp.incidence_east[toi] = sample.incidence_east.min(90.0);
+    sp.incidence_west[toi] = sample.incidence_west.min(90.0);
+    sp.zenith[toi] = sample.zenith.clamp(0.0, 90.0);
+    sp.azimuth[toi] = sample.azimuth;
+    sp.elevation[toi] = sample.elevation.max(0.0);
+    sp.sun_up[toi] = true;
+}
+
+/// Returns minute of day from local wall clock time
+///
+/// # Arguments
+///
+/// * 'date_time' - point in time
+fn minute_of_day(date_time: DateTime<Local>) -> usize {
+    (date_time.hour() * 60 + date_time.minute()) as usize
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use chrono::{DateTime, Local};
+use log::warn;
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::SolarPositions;
+use crate::manager_production::pipeline::SolarPositionModel;
+use crate::models::Parameters;
+
+const CACHE_PREFIX: &str = "solar";
+
+/// Number of solar position days kept in memory before the memory cache is cleared
+const MAX_MEMORY_ENTRIES: usize = 2000;
+
+/// Solar positions shared by all pipelines in the process, keyed as the disk cache files
+static MEMORY: OnceLock<Mutex<HashMap<String, SolarPositions>>> = OnceLock::new();
+
+/// Solar position stage that caches the result of an inner solar position model, in memory and
+/// on disk, keyed by model, date, site and plane orientation
+///
+pub struct CachedSolarPositions {
+    inner: Box<dyn SolarPositionModel>,
+    cache_dir: String,
+}
+
+impl CachedSolarPositions {
+    /// Returns a new instance of CachedSolarPositions
+    ///
+    /// # Arguments
+    ///
+    /// * 'inner' - solar position model to cache
+    /// * 'cache_dir' - directory to store/fetch cached solar positions to/from
+    pub fn new(inner: Box<dyn SolarPositionModel>, cache_dir: &str) -> Self {
+        Self { inner, cache_dir: cache_dir.to_string() }
+    }
+
+    /// Returns the cache key for the given date and parameters
+    ///
+    /// # Arguments
+    ///
+    /// * 'date_time' - date of the solar positions
+    /// * 'params' - parameters carrying site and orientation
+    fn key(&self, date_time: DateTime<Local>, params: &Parameters) -> String {
+        format!("{}-{}_{:.4}_{:.4}_{:.2}_{:.2}-{}",
+                CACHE_PREFIX, self.inner.id(),
+                params.lat, params.long, params.panel_slope, params.panel_east_azm,
+                date_time.format("%Y-%m-%d"))
+    }
+}
+
+impl SolarPositionModel for CachedSolarPositions {
+    fn solar_positions(&self, date_time: DateTime<Local>, params: &Parameters) -> Result<SolarPositions, ProdError> {
+        let key = self.key(date_time, params);
+        let memory = MEMORY.get_or_init(|| Mutex::new(HashMap::new()));
+
+        if let Some(sp) = memory.lock().unwrap().get(&key) {
+            return Ok(sp.clone());
+        }
+
+        let path = format!("{}{}.json", self.cache_dir, key);
+        let sp = match read_cache(&path) {
+            Some(sp) => sp,
+            None => {
+                let sp = self.inner.solar_positions(date_time, params)?;
+                if let Err(e) = write_cache(&path, &sp) {
+                    warn!("unable to write solar position cache {}: {}", path, e);
+                }
+                sp
+            }
+        };
+
+        let mut memory = memory.lock().unwrap();
+        if memory.len() >= MAX_MEMORY_ENTRIES {
+            memory.clear();
+        }
+        memory.insert(key, sp.clone());
+
+        Ok(sp)
+    }
+
+    fn id(&self) -> String {
+        self.inner.id()
+    }
+}
+
+/// Tries to read cached solar positions from file, unreadable files are treated as missing
+///
+/// # Arguments
+///
+/// * 'path' - path to the cache file
+fn read_cache(path: &str) -> Option<SolarPositions> {
+    let json = fs::read_to_string(path).ok()?;
+
+    serde_json::from_str(&json).ok()
+}
+
+/// Writes solar positions to a cache file
+///
+/// # Arguments
+///
+/// * 'path' - path to the cache file
+/// * 'sp' - solar positions to store
+fn write_cache(path: &str, sp: &SolarPositions) -> Result<(), std::io::Error> {
+    let json = serde_json::to_string(sp)?;
+    fs::write(path, json)
+}
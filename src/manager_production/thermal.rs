@@ -0,0 +1,323 @@
+use crate::manager_production::errors::ProdError;
+use crate::manager_production::models::{PlaneTemperature, ThermalState};
+use crate::manager_production::pipeline::ThermalModel;
+use crate::manager_production::sky::effective_air_temperature;
+use crate::models::Parameters;
+
+/// Lumped roof node where the panel temperature is taken to be the roof temperature
+///
+pub struct RoofRc;
+
+impl ThermalModel for RoofRc {
+    fn plane_temperature(&self, params: &Parameters, up: usize, incidence: &[f64;1440], sun_intensity: &[f64;1440], initial: Option<ThermalState>) -> Result<PlaneTemperature, ProdError> {
+        let roof = roof_temperature(params, Some(up), incidence, sun_intensity, initial.map(|s| s.roof))?;
+
+        Ok(PlaneTemperature { module: roof, roof })
+    }
+}
+
+/// Module node on rails above a roof node, each coupled to the other and to ambient air, the
+/// latter as experienced by a surface radiating to the sky
+///
+/// The module node is heated by the sun with gain `k_gain` and follows its equilibrium with
+/// time constant `tau_module`. The roof node is heated by the sun with gain `k_roof`, where it is
+/// not covered by the modules, and follows its equilibrium with the parameter time constants
+/// `tau` and `tau_down`. The `coupling` share in [0,1] of each node's equilibrium is taken from
+/// the other node instead of from ambient air.
+///
+pub struct TwoNode {
+    /// Module time constant [h]
+    pub tau_module: f64,
+    /// Roof °C boost at clear-sky normal incidence
+    pub k_roof: f64,
+    /// Share of each node's equilibrium given by the other node
+    pub coupling: f64,
+}
+
+impl ThermalModel for TwoNode {
+    fn plane_temperature(&self, params: &Parameters, up: usize, incidence: &[f64;1440], sun_intensity: &[f64;1440], initial: Option<ThermalState>) -> Result<PlaneTemperature, ProdError> {
+        two_node_thermodynamics(
+            &effective_air_temperature(params),
+            incidence,
+            sun_intensity,
+            60.0,
+            self.tau_module * 3600.0,
+            params.k_gain,
+            params.tau * 3600.0,
+            params.tau_down * 3600.0,
+            self.k_roof,
+            self.coupling,
+            params.clouds.as_ref(),
+            up,
+            initial)
+    }
+}
+
+/// Calculates roof temperature given ambient temperature, radiative cooling to the sky and effect
+/// from direct sunlight
+///
+/// # Arguments
+///
+/// * 'params' - parameters
+/// * 'up' - time when the sun is free from obstacles
+/// * 'inc_deg' - sun incidence on panels in degrees
+/// * 'sif' - sun intensity factor
+/// * 't0' - roof temperature at midnight, if known
+fn roof_temperature(params: &Parameters, up: Option<usize>, inc_deg: &[f64;1440], sif: &[f64;1440], t0: Option<f64>) -> Result<[f64;1440], ProdError> {
+
+    let t_roof = roof_thermodynamics(
+        &effective_air_temperature(params),
+        inc_deg,
+        sif,
+        60.0,
+        params.tau * 3600.0,
+        params.k_gain,
+        params.clouds.as_ref().map(|c| c.as_slice()),
+        t0,
+        Some(params.tau_down * 3600.0),
+        up)?;
+
+    let mut result: [f64;1440] = [0.0; 1440];
+    (0..1440)
+        .for_each(|i| {
+            result[i] = t_roof[i];
+        });
+
+    Ok(result)
+}
+
+/// Roof temperature over time using a 1st-order thermal RC model.
+///
+/// State update (explicit Euler):
+///   T_roof[k] = T_roof[k-1] + (T_eq - T_roof[k-1]) * (dt / tau_eff)
+/// where:
+///   T_eq = T_air[k] + K * max(0, cos(inc_deg[k])) * clouds[k]
+///   tau_eff = tau (when heating) or tau_down.unwrap_or(tau) (when cooling)
+///
+/// Notes:
+/// - inc_deg is the sun incidence angle relative to the roof normal (0 deg = perpendicular to roof).
+///   For a horizontal roof, inc_deg = 90 - altitude_deg.
+/// - cos(inc_deg) gives the direct-beam projection onto the roof plane and is clamped at 0.
+///
+/// # Arguments
+/// * `t_air`    : ambient air temperature including radiative exchange with the sky [°C], length N
+/// * `inc_deg`  : sun incidence angle to the roof normal [degrees], length N
+/// * `sif`      : sun intensity factor, length N
+/// * `dt`       : timestep [s], e.g. 600.0
+/// * `tau`      : time constant for heating [s]
+/// * `k_gain`   : °C boost at clear-sky normal incidence (proxy for A*α*G_max/U)
+/// * `clouds`   : optional attenuation array in [0,1], length N (defaults to 1.0)
+/// * `t0`       : optional initial roof temperature [°C] (defaults to t_air[0])
+/// * `tau_down` : optional time constant for cooling [s] (defaults to `tau`)
+///
+/// # Returns
+///
+/// Vector of roof temperatures [°C], length N.
+///
+/// # Panics
+///
+/// Panics if input lengths mismatch or if `dt <= 0.0` or any tau ≤ 0.0.
+#[allow(clippy::too_many_arguments)]
+fn roof_thermodynamics(
+    t_air: &[f64],
+    inc_deg: &[f64],
+    sif: &[f64],
+    dt: f64,
+    tau: f64,
+    k_gain: f64,
+    clouds: Option<&[f64]>,
+    t0: Option<f64>,
+    tau_down: Option<f64>,
+    up: Option<usize>,
+) -> Result<Vec<f64>, ProdError> {
+    let n = t_air.len();
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    // Check arrays lengths and input values
+    if inc_deg.len() != n || sif.len() != n {
+        return Err("inc_rad and sif must have the same length as t_air".into());
+    }
+    if let Some(c) = clouds && c.len() != n {
+        return Err("clouds must have the same length as t_air".into());
+    }
+    if dt <= 0.0 {
+        return Err("dt must be > 0".into());
+    }
+    if tau <= 0.0 {
+        return Err("tau must be > 0".into());
+    }
+    if let Some(td) = tau_down && td <= 0.0 {
+        return Err("tau_down must be > 0".into());
+    }
+
+    let mut t_roof = vec![0.0; n];
+    t_roof[0] = t0.unwrap_or(t_air[0]);
+    let tau_cool = tau_down.unwrap_or(tau);
+    let up_delay = up.unwrap_or(0);
+
+    for k in 1..n {
+        // clouds[k] defaults to 1.0 if not provided
+        let cloud_k = clouds.map_or(1.0, |c| c[k]);
+
+        // Use projection by incidence: cos(inc_rad), clamped to [0, +inf) at 0.
+        let inc_deg_k = if k <= up_delay {
+            90.0
+        } else {
+            inc_deg[k]
+        };
+
+        let projection = inc_deg_k.to_radians().cos().max(0.0);
+        let sun_boost = k_gain * projection * cloud_k; // [°C]
+
+        let t_eq = t_air[k] + sun_boost * sif[k];
+
+        let tau_eff = if t_eq > t_roof[k - 1] { tau } else { tau_cool };
+        let alpha = dt / tau_eff; // Euler gain
+
+        t_roof[k] = t_roof[k - 1] + (t_eq - t_roof[k - 1]) * alpha;
+    }
+
+    Ok(t_roof)
+}
+
+/// Module and roof temperature over time using a 2nd-order thermal RC model.
+///
+/// State update (explicit Euler, both nodes from the previous step):
+///   T_mod[k]  = T_mod[k-1]  + (T_eq_mod  - T_mod[k-1])  * (dt / tau_module)
+///   T_roof[k] = T_roof[k-1] + (T_eq_roof - T_roof[k-1]) * (dt / tau_roof)
+/// where:
+///   T_eq_mod  = (1 - c) * T_air[k] + c * T_roof[k-1] + K_mod * S[k]
+///   T_eq_roof = (1 - c) * T_air[k] + c * T_mod[k-1]  + K_roof * S[k]
+///   S[k]      = max(0, cos(inc_deg[k])) * sif[k] * clouds[k]
+///   tau_roof  = tau_roof_up (when heating) or tau_roof_down (when cooling)
+///
+/// As for the one-node model, until `up` the sun is taken to be blocked, and without an initial
+/// state both nodes start at the first air temperature.
+/// Euler gains are capped at 1 so short time constants cannot make the update overshoot.
+///
+/// # Arguments
+/// * `t_air`         : ambient air temperature including radiative exchange with the sky [°C]
+/// * `inc_deg`       : sun incidence angle to the plane normal [degrees], length N
+/// * `sif`           : sun intensity factor, length N
+/// * `dt`            : timestep [s]
+/// * `tau_module`    : module time constant [s]
+/// * `k_module`      : module °C boost at clear-sky normal incidence
+/// * `tau_roof_up`   : roof time constant for heating [s]
+/// * `tau_roof_down` : roof time constant for cooling [s]
+/// * `k_roof`        : roof °C boost at clear-sky normal incidence
+/// * `coupling`      : share of each node's equilibrium given by the other node, in [0,1]
+/// * `clouds`        : optional attenuation array in [0,1] (defaults to 1.0)
+/// * `up`            : time when the sun is free from obstacles
+/// * `initial`       : optional node temperatures at the first timestep [°C]
+#[allow(clippy::too_many_arguments)]
+fn two_node_thermodynamics(
+    t_air: &[f64;1440],
+    inc_deg: &[f64;1440],
+    sif: &[f64;1440],
+    dt: f64,
+    tau_module: f64,
+    k_module: f64,
+    tau_roof_up: f64,
+    tau_roof_down: f64,
+    k_roof: f64,
+    coupling: f64,
+    clouds: Option<&[f64;1440]>,
+    up: usize,
+    initial: Option<ThermalState>,
+) -> Result<PlaneTemperature, ProdError> {
+    if dt <= 0.0 {
+        return Err("dt must be > 0".into());
+    }
+    if tau_module <= 0.0 || tau_roof_up <= 0.0 || tau_roof_down <= 0.0 {
+        return Err("module and roof time constants must be > 0".into());
+    }
+    if !(0.0..=1.0).contains(&coupling) {
+        return Err("coupling must be within [0,1]".into());
+    }
+
+    let mut module: [f64;1440] = [0.0; 1440];
+    let mut roof: [f64;1440] = [0.0; 1440];
+    let start = initial.unwrap_or(ThermalState { module: t_air[0], roof: t_air[0] });
+    module[0] = start.module;
+    roof[0] = start.roof;
+
+    let alpha_module = (dt / tau_module).min(1.0);
+
+    for k in 1..1440 {
+        let sun_k = if k <= up {
+            0.0
+        } else {
+            inc_deg[k].to_radians().cos().max(0.0) * sif[k] * clouds.map_or(1.0, |c| c[k])
+        };
+        let t_air_k = t_air[k];
+
+        let t_eq_module = (1.0 - coupling) * t_air_k + coupling * roof[k - 1] + k_module * sun_k;
+        let t_eq_roof = (1.0 - coupling) * t_air_k + coupling * module[k - 1] + k_roof * sun_k;
+
+        let tau_roof = if t_eq_roof > roof[k - 1] { tau_roof_up } else { tau_roof_down };
+        let alpha_roof = (dt / tau_roof).min(1.0);
+
+        module[k] = module[k - 1] + (t_eq_module - module[k - 1]) * alpha_module;
+        roof[k] = roof[k - 1] + (t_eq_roof - roof[k - 1]) * alpha_roof;
+    }
+
+    Ok(PlaneTemperature { module, roof })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parameters for a mild day with the sun at normal incidence from 06:00 to 18:00
+    fn sunny_day(clouds: Option<[f64;1440]>) -> (Parameters, [f64;1440], [f64;1440]) {
+        let params = Parameters {
+            year: 2025,
+            month: 6,
+            day: 2,
+            lat: 56.0,
+            long: 13.0,
+            temp: [15.0; 1440],
+            temp_previous: None,
+            humidity: None,
+            cloud_cover: None,
+            clouds,
+            panel_power: 430.0,
+            panel_slope: 40.5,
+            panel_east_azm: -82.5,
+            panel_temp_red: 0.35,
+            tau: 0.5,
+            tau_down: 0.8,
+            k_gain: 30.0,
+            iam_factor: 2.0,
+            start_azm: 100.0,
+            start_elv: 10.0,
+            stop_azm: 280.0,
+            stop_elv: 10.0,
+        };
+        let incidence: [f64;1440] = std::array::from_fn(|m| if (360..1080).contains(&m) { 0.0 } else { 90.0 });
+        let sun_intensity: [f64;1440] = std::array::from_fn(|m| if (360..1080).contains(&m) { 1.0 } else { 0.0 });
+
+        (params, incidence, sun_intensity)
+    }
+
+    #[test]
+    fn cloud_attenuation_cools_panels() {
+        let models: [Box<dyn ThermalModel>; 2] = [
+            Box::new(RoofRc),
+            Box::new(TwoNode { tau_module: 0.1, k_roof: 20.0, coupling: 0.5 }),
+        ];
+
+        for model in models {
+            let (clear, incidence, sun_intensity) = sunny_day(None);
+            let (cloudy, _, _) = sunny_day(Some([0.5; 1440]));
+
+            let clear = model.plane_temperature(&clear, 0, &incidence, &sun_intensity, None).unwrap();
+            let cloudy = model.plane_temperature(&cloudy, 0, &incidence, &sun_intensity, None).unwrap();
+
+            assert!(cloudy.module[720] < clear.module[720] - 5.0);
+            assert!((0..360).all(|m| cloudy.module[m] == clear.module[m]));
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::manager_production::models::PlaneIrradiance;
+use crate::manager_production::pipeline::TranspositionModel;
+
+/// Share of clear sky intensity assumed to be diffuse from the sky dome
+const DIFFUSE_SHARE: f64 = 0.1;
+
+/// All intensity treated as beam, the projection onto the plane is left to the IAM stage
+///
+pub struct Beam;
+
+impl TranspositionModel for Beam {
+    fn plane_irradiance(&self, sun_intensity: f64, _zenith: f64, _slope: f64) -> PlaneIrradiance {
+        PlaneIrradiance { beam: sun_intensity, diffuse: 0.0 }
+    }
+}
+
+/// Intensity split into beam and an isotropic sky diffuse part seen by the plane
+///
+pub struct Isotropic;
+
+impl TranspositionModel for Isotropic {
+    fn plane_irradiance(&self, sun_intensity: f64, zenith: f64, slope: f64) -> PlaneIrradiance {
+        if zenith >= 90.0 {
+            return PlaneIrradiance { beam: 0.0, diffuse: 0.0 };
+        }
+
+        let sky_view = (1.0 + slope.to_radians().cos()) / 2.0;
+
+        PlaneIrradiance {
+            beam: sun_intensity * (1.0 - DIFFUSE_SHARE),
+            diffuse: sun_intensity * DIFFUSE_SHARE * sky_view,
+        }
+    }
+}
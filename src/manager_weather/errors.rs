@@ -19,4 +19,7 @@ impl From<serde_json::Error> for WeatherError {
 }
 impl From<std::io::Error> for WeatherError {
     fn from(e: std::io::Error) -> Self { WeatherError(e.to_string()) }
+}
+impl From<String> for WeatherError {
+    fn from(e: String) -> Self { WeatherError(e) }
 }
\ No newline at end of file
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde_json::{Map, Value};
+use tokio::fs::read_to_string;
+use crate::manager_weather::source::{SourceFuture, WeatherSource, WeatherVariable};
+use crate::models::DataItem;
+
+/// Local time formats accepted in weather files besides RFC 3339
+const TIME_FORMATS: [&str; 3] = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"];
+
+/// Time and the values of the variables given at that time, one line or object of a file
+type Row = (DateTime<Local>, Vec<(WeatherVariable, f64)>);
+
+/// Local weather file, read anew for every request so it can be appended to
+///
+/// A `.json` file holds an array of objects with the time in `x` and either the temperature in
+/// `y`, like the logger service, or values keyed by variable name such as `wind_speed`. Any other
+/// file is CSV with a time and values per line, separated by comma or, with decimal comma, by
+/// semicolon. A header line naming the variables selects the columns, without one the single
+/// value is the temperature. Other lines that do not parse are skipped.
+///
+pub struct WeatherFile {
+    path: String,
+}
+
+impl WeatherFile {
+    /// Returns a new instance of WeatherFile
+    ///
+    /// # Arguments
+    ///
+    /// * 'path' - path to the weather file
+    pub fn new(path: &str) -> Self {
+        Self { path: path.to_string() }
+    }
+}
+
+impl WeatherSource for WeatherFile {
+    fn variables(&self) -> &[WeatherVariable] {
+        &WeatherVariable::ALL
+    }
+
+    fn cache_id(&self) -> Option<&'static str> {
+        Some("file")
+    }
+
+    fn settle_days(&self) -> u64 {
+        0
+    }
+
+    fn history<'a>(&'a self, variables: &'a [WeatherVariable], _from: DateTime<Local>, _to: DateTime<Local>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            let text = read_to_string(&self.path).await
+                .map_err(|e| format!("unable to read {}: {}", self.path, e))?;
+
+            let rows = if self.path.ends_with(".json") {
+                let items: Vec<Map<String, Value>> = serde_json::from_str(&text)?;
+                items.iter().filter_map(json_row).collect::<Vec<Row>>()
+            } else {
+                csv_rows(&text)
+            };
+
+            Ok(variables.iter()
+                .map(|v| (*v, rows.iter()
+                    .filter_map(|(x, values)| values.iter().find(|(w, _)| w == v).map(|(_, y)| DataItem { x: *x, y: *y }))
+                    .collect::<Vec<DataItem>>()))
+                .filter(|(_, samples)| !samples.is_empty())
+                .collect())
+        })
+    }
+}
+
+/// Returns the time and values of a JSON weather object, if it has a time
+///
+/// # Arguments
+///
+/// * 'item' - the JSON object
+fn json_row(item: &Map<String, Value>) -> Option<Row> {
+    let x = parse_time(item.get("x")?.as_str()?)?;
+    let mut values = WeatherVariable::ALL.iter()
+        .filter_map(|v| Some((*v, item.get(v.name())?.as_f64()?)))
+        .collect::<Vec<(WeatherVariable, f64)>>();
+    if let Some(y) = item.get("y").and_then(|y| y.as_f64()) {
+        values.push((WeatherVariable::Temperature, y));
+    }
+
+    Some((x, values))
+}
+
+/// Returns the time and values of each line of a CSV weather file that holds them
+///
+/// # Arguments
+///
+/// * 'text' - content of the file
+fn csv_rows(text: &str) -> Vec<Row> {
+    let mut columns: Vec<Option<WeatherVariable>> = vec![Some(WeatherVariable::Temperature)];
+    let mut result: Vec<Row> = Vec::new();
+
+    for line in text.lines() {
+        let fields = split_line(line);
+        let Some(x) = fields.first().and_then(|f| parse_time(f)) else {
+            let header = fields.iter()
+                .skip(1)
+                .map(|f| WeatherVariable::ALL.into_iter().find(|v| v.name() == f.as_str()))
+                .collect::<Vec<Option<WeatherVariable>>>();
+            if header.iter().any(|h| h.is_some()) {
+                columns = header;
+            }
+            continue;
+        };
+
+        let values = columns.iter()
+            .zip(fields.iter().skip(1))
+            .filter_map(|(c, f)| Some(((*c)?, f.parse::<f64>().ok()?)))
+            .collect::<Vec<(WeatherVariable, f64)>>();
+        result.push((x, values));
+    }
+
+    result
+}
+
+/// Returns the fields of a CSV line, a semicolon separated line using decimal comma
+///
+/// # Arguments
+///
+/// * 'line' - the line
+fn split_line(line: &str) -> Vec<String> {
+    let clean = |f: &str| f.trim().trim_matches('"').to_string();
+
+    if line.contains(';') {
+        line.split(';').map(|f| clean(f).replace(',', ".")).collect()
+    } else {
+        line.split(',').map(clean).collect()
+    }
+}
+
+/// Returns a point in time from RFC 3339 or a local time in one of the accepted formats
+///
+/// # Arguments
+///
+/// * 'text' - the time as text
+fn parse_time(text: &str) -> Option<DateTime<Local>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Local))
+        .or_else(|| TIME_FORMATS.iter()
+            .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+            .and_then(|t| t.and_local_timezone(Local).earliest()))
+}
@@ -0,0 +1,69 @@
+use std::time::Duration;
+use chrono::{DateTime, Local};
+use reqwest::Client;
+use crate::manager_weather::errors::WeatherError;
+use crate::manager_weather::models::WeatherItem;
+use crate::manager_weather::source::{SourceFuture, WeatherSource, WeatherVariable};
+use crate::models::DataItem;
+
+/// Home-made weather logger service, temperature only
+///
+pub struct Logger {
+    client: Client,
+    host: String,
+    sensor: String,
+}
+
+impl Logger {
+    /// Returns a new instance of Logger
+    ///
+    /// # Arguments
+    ///
+    /// * 'host' - host running the weather logger service
+    /// * 'sensor' - name of sensor to get weather data for
+    pub fn new(host: &str, sensor: &str) -> Result<Self, WeatherError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+
+        Ok(Self { client, host: host.to_string(), sensor: sensor.to_string() })
+    }
+}
+
+impl WeatherSource for Logger {
+    fn variables(&self) -> &[WeatherVariable] {
+        &[WeatherVariable::Temperature]
+    }
+
+    fn cache_id(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn settle_days(&self) -> u64 {
+        0
+    }
+
+    fn history<'a>(&'a self, variables: &'a [WeatherVariable], from: DateTime<Local>, to: DateTime<Local>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            if !variables.contains(&WeatherVariable::Temperature) {
+                return Ok(Vec::new());
+            }
+
+            let url = format!("http://{}/temperature", self.host);
+
+            let req = self.client.get(&url)
+                .query(&[("id", &self.sensor), ("from", &from.to_rfc3339()), ("to", &to.to_rfc3339())])
+                .send().await?;
+
+            let status = req.status();
+            if !status.is_success() {
+                return Err(WeatherError(format!("{:?}", status)));
+            }
+
+            let json = req.text().await?;
+            let weather_res: Vec<WeatherItem> = serde_json::from_str(&json)?;
+
+            Ok(vec![(WeatherVariable::Temperature, weather_res.into_iter().map(|w| DataItem { x: w.x, y: w.y }).collect())])
+        })
+    }
+}
@@ -1,39 +1,65 @@
 pub mod errors;
 mod models;
+pub mod source;
+mod logger;
+mod file;
+mod open_meteo;
+mod smhi;
 
 use std::ops::Add;
-use std::time::Duration;
-use chrono::{DateTime, DurationRound, Local, TimeDelta};
-use reqwest::Client;
+use chrono::{DateTime, Days, DurationRound, Local, TimeDelta};
 use crate::cache::{read_cache_data, store_cache_data};
+use crate::initialization::{GeoRef, Weather as WeatherConfig};
 use crate::manager_weather::errors::WeatherError;
-use crate::manager_weather::models::WeatherItem;
+use crate::manager_weather::file::WeatherFile;
+use crate::manager_weather::logger::Logger;
+use crate::manager_weather::open_meteo::OpenMeteo;
+use crate::manager_weather::smhi::Smhi;
+use crate::manager_weather::source::{WeatherSource, WeatherSourceChoice, WeatherVariable};
 use crate::models::DataItem;
 
-const CACHE_PREFIX: &str = "temp";
+/// Weather variables of one day per minute, those the source did not have left out
+///
+pub struct WeatherBundle {
+    series: Vec<(WeatherVariable, [f64;1440])>,
+}
+
+impl WeatherBundle {
+    /// Returns a variable per minute, if the source had it for the day
+    ///
+    /// # Arguments
+    ///
+    /// * 'variable' - the weather variable
+    pub fn get(&self, variable: WeatherVariable) -> Option<[f64;1440]> {
+        self.series.iter().find(|(v, _)| *v == variable).map(|(_, s)| *s)
+    }
+}
 
 /// Weather manager
 /// 
 pub struct Weather {
-    client: Client,
-    host: String,
-    sensor: String,
+    source: Box<dyn WeatherSource>,
 }
 
 impl Weather {
 
-    /// Returns a new instance of Weather
+    /// Returns a new instance of Weather using the source given in the configuration
     /// 
     /// # Arguments
     /// 
-    /// * 'host' - host running the weather logger service
-    /// * 'sensor' - name of sensor to get weather data for
-    pub fn new(host: &str, sensor: &str) -> Result<Self, WeatherError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()?;
-        
-        Ok(Self { client, host: host.to_string(), sensor: sensor.to_string() })
+    /// * 'config' - weather configuration struct
+    /// * 'geo_ref' - site location, used by sources covering an area
+    pub fn new(config: &WeatherConfig, geo_ref: &GeoRef) -> Result<Self, WeatherError> {
+        let source: Box<dyn WeatherSource> = match config.source {
+            WeatherSourceChoice::Logger => Box::new(Logger::new(&config.host, &config.sensor)?),
+            WeatherSourceChoice::File => Box::new(WeatherFile::new(
+                config.path.as_deref().ok_or("weather source file needs a path")?)),
+            WeatherSourceChoice::OpenMeteo => Box::new(OpenMeteo::new(config.url.as_deref(), geo_ref.lat, geo_ref.long)?),
+            WeatherSourceChoice::Smhi => Box::new(Smhi::new(
+                config.url.as_deref(), config.station.as_deref().ok_or("weather source smhi needs a station")?)?),
+        };
+
+        Ok(Self { source })
     }
     
     /// Returns the temperature history from the given date
@@ -43,33 +69,81 @@ impl Weather {
     /// * 'date_time' - date to get history for
     /// * 'cache_dir' - directory to store/fetch existing date to/from
     pub async fn get_temp_history(&self, date_time: DateTime<Local>, cache_dir: &str) -> Result<[f64;1440], WeatherError> {
-        let result = if let Some(result) = read_cache_data(cache_dir, CACHE_PREFIX, date_time).await? {
-            result
-        } else {
-            let url = format!("http://{}/temperature", self.host);
-
-            let from = date_time.duration_trunc(TimeDelta::days(1)).unwrap();
-            let to = from.add(TimeDelta::days(1)).add(TimeDelta::minutes(-1));
+        self.get_variables(date_time, cache_dir, &[WeatherVariable::Temperature]).await?
+            .get(WeatherVariable::Temperature)
+            .ok_or("no temperature history".into())
+    }
 
-            let req = self.client.get(&url)
-                .query(&[("id", &self.sensor), ("from", &from.to_rfc3339()), ("to", &to.to_rfc3339())])
-                .send().await?;
+    /// Returns the history of every weather variable the source has from the given date
+    ///
+    /// # Arguments
+    ///
+    /// * 'date_time' - date to get history for
+    /// * 'cache_dir' - directory to store/fetch existing date to/from
+    pub async fn get_history(&self, date_time: DateTime<Local>, cache_dir: &str) -> Result<WeatherBundle, WeatherError> {
+        self.get_variables(date_time, cache_dir, &WeatherVariable::ALL).await
+    }
 
-            let status = req.status();
-            if !status.is_success() {
-                return Err(WeatherError(format!("{:?}", status)));
+    /// Returns the history of the given weather variables from the given date, each cached
+    /// separately and only those not cached fetched from the source
+    ///
+    /// A variable the source does not have for the day is left out of the bundle, and cached empty
+    /// so that it is not asked for again once the source can no longer add history for the day.
+    ///
+    /// # Arguments
+    ///
+    /// * 'date_time' - date to get history for
+    /// * 'cache_dir' - directory to store/fetch existing date to/from
+    /// * 'variables' - weather variables to get
+    async fn get_variables(&self, date_time: DateTime<Local>, cache_dir: &str, variables: &[WeatherVariable]) -> Result<WeatherBundle, WeatherError> {
+        let mut histories: Vec<(WeatherVariable, Vec<DataItem>)> = Vec::new();
+        let mut missing: Vec<WeatherVariable> = Vec::new();
+
+        for variable in variables.iter().filter(|v| self.source.variables().contains(v)) {
+            match read_cache_data(cache_dir, &self.cache_prefix(*variable), date_time).await? {
+                Some(result) if result.is_empty() => (),
+                Some(result) => histories.push((*variable, result)),
+                None => missing.push(*variable),
             }
+        }
 
-            let json = req.text().await?;
-            let weather_res: Vec<WeatherItem> = serde_json::from_str(&json)?;
+        if !missing.is_empty() {
+            let from = date_time.duration_trunc(TimeDelta::days(1)).unwrap();
+            let to = from.add(TimeDelta::days(1)).add(TimeDelta::minutes(-1));
 
-            let result = transform_history(weather_res, from, to);
-            store_cache_data(cache_dir, CACHE_PREFIX, date_time, &result).await?;
+            let settled = date_time.date_naive() + Days::new(self.source.settle_days()) < Local::now().date_naive();
+
+            let mut fetched = self.source.history(&missing, from, to).await?;
+            for variable in missing {
+                let result = fetched.iter()
+                    .position(|(v, _)| *v == variable)
+                    .map(|i| transform_history(fetched.swap_remove(i).1, from, to))
+                    .unwrap_or_default();
+                if !result.is_empty() || settled {
+                    store_cache_data(cache_dir, &self.cache_prefix(variable), date_time, &result).await?;
+                }
+                if !result.is_empty() {
+                    histories.push((variable, result));
+                }
+            }
+        }
 
-            result
-        };
+        Ok(WeatherBundle {
+            series: histories.into_iter().map(|(v, h)| (v, fill_minutes(h))).collect(),
+        })
+    }
 
-        Ok(fill_minutes(result))
+    /// Returns the prefix of a variable's cache files, specific to the source so that switching
+    /// source does not pick up what another source had or lacked
+    ///
+    /// # Arguments
+    ///
+    /// * 'variable' - weather variable to get the prefix for
+    fn cache_prefix(&self, variable: WeatherVariable) -> String {
+        match self.source.cache_id() {
+            Some(id) => format!("{}-{}", variable.cache_prefix(), id),
+            None => variable.cache_prefix().to_string(),
+        }
     }
 }
 
@@ -104,17 +178,19 @@ fn fill_minutes(data: Vec<DataItem>) -> [f64;1440] {
 /// Transforms the history from the weather database to a per minute vector
 ///
 /// While doing so the transformation also ensures that the 'to' date has a data item, and
-/// possibly also the 'from' date
+/// possibly also the 'from' date. Samples outside the period are left out.
 /// 
 /// # Arguments
 /// 
 /// * 'history' - the history data to transform
 /// * 'from' - from date to include with a data item
 /// * 'to' - to date to include with a data item
-fn transform_history(history: Vec<WeatherItem>, from: DateTime<Local>, to: DateTime<Local>) -> Vec<DataItem> {
+fn transform_history(mut history: Vec<DataItem>, from: DateTime<Local>, to: DateTime<Local>) -> Vec<DataItem> {
     let mut result: Vec<DataItem> = Vec::new();
+    history.retain(|w| w.x >= from && w.x < to + TimeDelta::minutes(1));
+    history.sort_by_key(|w| w.x);
     
-    if history.len() == 0 {
+    if history.is_empty() {
         result
     } else {
         history.into_iter().for_each(|w| {
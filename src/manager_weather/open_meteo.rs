@@ -0,0 +1,125 @@
+use std::time::Duration;
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use crate::manager_weather::errors::WeatherError;
+use crate::manager_weather::source::{SourceFuture, WeatherSource, WeatherVariable};
+use crate::models::DataItem;
+
+const ARCHIVE_URL: &str = "https://archive-api.open-meteo.com/v1/archive";
+
+#[derive(Deserialize)]
+struct ArchiveDocument {
+    /// Times and one array of values per requested variable, keyed by Open-Meteo name
+    hourly: Map<String, Value>,
+}
+
+/// Open-Meteo historical weather archive, hourly reanalysis values for the site
+///
+/// See https://open-meteo.com/en/docs/historical-weather-api
+///
+pub struct OpenMeteo {
+    client: Client,
+    url: String,
+    lat: f64,
+    long: f64,
+}
+
+impl OpenMeteo {
+    /// Returns a new instance of OpenMeteo
+    ///
+    /// # Arguments
+    ///
+    /// * 'url' - archive API url, if none the public Open-Meteo archive
+    /// * 'lat' - latitude of the site
+    /// * 'long' - longitude of the site
+    pub fn new(url: Option<&str>, lat: f64, long: f64) -> Result<Self, WeatherError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+
+        Ok(Self { client, url: url.unwrap_or(ARCHIVE_URL).to_string(), lat, long })
+    }
+}
+
+impl WeatherSource for OpenMeteo {
+    fn variables(&self) -> &[WeatherVariable] {
+        &WeatherVariable::ALL
+    }
+
+    fn cache_id(&self) -> Option<&'static str> {
+        Some("open_meteo")
+    }
+
+    fn settle_days(&self) -> u64 {
+        // The archive lags behind by about five days
+        7
+    }
+
+    fn history<'a>(&'a self, variables: &'a [WeatherVariable], from: DateTime<Local>, to: DateTime<Local>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            let (start, end) = (from.with_timezone(&Utc).date_naive(), to.with_timezone(&Utc).date_naive());
+            let hourly = variables.iter().map(|v| open_meteo_name(*v).0).collect::<Vec<&str>>().join(",");
+
+            let req = self.client.get(&self.url)
+                .query(&[
+                    ("latitude", self.lat.to_string()),
+                    ("longitude", self.long.to_string()),
+                    ("start_date", start.to_string()),
+                    ("end_date", end.to_string()),
+                    ("hourly", hourly),
+                    ("wind_speed_unit", "ms".to_string()),
+                    ("timezone", "GMT".to_string()),
+                ])
+                .send().await?;
+
+            let status = req.status();
+            if !status.is_success() {
+                return Err(WeatherError(format!("{:?}", status)));
+            }
+
+            let document: ArchiveDocument = serde_json::from_str(&req.text().await?)?;
+            let time = document.hourly.get("time")
+                .and_then(|t| t.as_array())
+                .ok_or("open-meteo document without hourly times")?
+                .iter()
+                .map(|t| {
+                    let t = t.as_str().unwrap_or_default();
+                    NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M")
+                        .map(|t| t.and_utc().with_timezone(&Local))
+                        .map_err(|e| WeatherError(format!("invalid time {}: {}", t, e)))
+                })
+                .collect::<Result<Vec<DateTime<Local>>, WeatherError>>()?;
+
+            Ok(variables.iter()
+                .filter_map(|v| {
+                    let (name, scale) = open_meteo_name(*v);
+                    let values = document.hourly.get(name)?.as_array()?;
+                    let samples = time.iter()
+                        .zip(values.iter())
+                        .filter_map(|(x, y)| Some(DataItem { x: *x, y: y.as_f64()? * scale }))
+                        .collect::<Vec<DataItem>>();
+                    Some((*v, samples))
+                })
+                .collect())
+        })
+    }
+}
+
+/// Returns the Open-Meteo name of a variable and the factor to its unit here
+///
+/// # Arguments
+///
+/// * 'variable' - the weather variable
+fn open_meteo_name(variable: WeatherVariable) -> (&'static str, f64) {
+    match variable {
+        WeatherVariable::Temperature => ("temperature_2m", 1.0),
+        WeatherVariable::WindSpeed => ("wind_speed_10m", 1.0),
+        WeatherVariable::CloudCover => ("cloud_cover", 0.01),
+        WeatherVariable::GlobalIrradiance => ("shortwave_radiation", 1.0),
+        WeatherVariable::Humidity => ("relative_humidity_2m", 1.0),
+        WeatherVariable::Pressure => ("surface_pressure", 1.0),
+        WeatherVariable::Precipitation => ("precipitation", 1.0),
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+use chrono::{DateTime, Local, TimeZone};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use crate::manager_weather::errors::WeatherError;
+use crate::manager_weather::source::{SourceFuture, WeatherSource, WeatherVariable};
+use crate::models::DataItem;
+
+const OBSERVATIONS_URL: &str = "https://opendata-download-metobs.smhi.se/api/version/1.0";
+
+#[derive(Deserialize)]
+struct Observation {
+    /// Milliseconds since the epoch
+    date: i64,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct ObservationDocument {
+    value: Option<Vec<Observation>>,
+}
+
+/// SMHI meteorological observations for one station, the latest four months
+///
+/// Each variable is a parameter of its own, stations lacking a parameter are skipped for it.
+/// See https://opendata.smhi.se/apidocs/metobs/
+///
+pub struct Smhi {
+    client: Client,
+    url: String,
+    station: String,
+}
+
+impl Smhi {
+    /// Returns a new instance of Smhi
+    ///
+    /// # Arguments
+    ///
+    /// * 'url' - observations API url, if none the public SMHI open data API
+    /// * 'station' - id of the station
+    pub fn new(url: Option<&str>, station: &str) -> Result<Self, WeatherError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()?;
+
+        Ok(Self { client, url: url.unwrap_or(OBSERVATIONS_URL).to_string(), station: station.to_string() })
+    }
+}
+
+impl WeatherSource for Smhi {
+    fn variables(&self) -> &[WeatherVariable] {
+        &WeatherVariable::ALL
+    }
+
+    fn cache_id(&self) -> Option<&'static str> {
+        Some("smhi")
+    }
+
+    fn settle_days(&self) -> u64 {
+        // Observations are quality controlled and completed during the latest four months
+        124
+    }
+
+    fn history<'a>(&'a self, variables: &'a [WeatherVariable], _from: DateTime<Local>, _to: DateTime<Local>) -> SourceFuture<'a> {
+        Box::pin(async move {
+            let mut result: Vec<(WeatherVariable, Vec<DataItem>)> = Vec::new();
+
+            for variable in variables {
+                let (parameter, scale) = smhi_parameter(*variable);
+                let url = format!("{}/parameter/{}/station/{}/period/latest-months/data.json", self.url, parameter, self.station);
+
+                let req = self.client.get(&url).send().await?;
+
+                let status = req.status();
+                if status == StatusCode::NOT_FOUND {
+                    continue;
+                }
+                if !status.is_success() {
+                    return Err(WeatherError(format!("{:?}", status)));
+                }
+
+                let document: ObservationDocument = serde_json::from_str(&req.text().await?)?;
+                let samples = document.value.unwrap_or_default().iter()
+                    .filter_map(|o| Some(DataItem {
+                        x: Local.timestamp_millis_opt(o.date).single()?,
+                        y: o.value.parse::<f64>().ok()? * scale,
+                    }))
+                    .collect();
+                result.push((*variable, samples));
+            }
+
+            Ok(result)
+        })
+    }
+}
+
+/// Returns the SMHI parameter of a variable and the factor to its unit here
+///
+/// # Arguments
+///
+/// * 'variable' - the weather variable
+fn smhi_parameter(variable: WeatherVariable) -> (u32, f64) {
+    match variable {
+        WeatherVariable::Temperature => (1, 1.0),
+        WeatherVariable::WindSpeed => (4, 1.0),
+        WeatherVariable::CloudCover => (16, 0.01),
+        WeatherVariable::GlobalIrradiance => (11, 1.0),
+        WeatherVariable::Humidity => (6, 1.0),
+        WeatherVariable::Pressure => (9, 1.0),
+        WeatherVariable::Precipitation => (7, 1.0),
+    }
+}
@@ -0,0 +1,103 @@
+use std::future::Future;
+use std::pin::Pin;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use crate::manager_weather::errors::WeatherError;
+use crate::models::DataItem;
+
+/// Future returned by weather sources, boxed so that sources can be chosen at runtime
+pub type SourceFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<(WeatherVariable, Vec<DataItem>)>, WeatherError>> + Send + 'a>>;
+
+/// Weather variable with the unit every source returns it in
+///
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherVariable {
+    /// Air temperature [°C]
+    Temperature,
+    /// Wind speed [m/s]
+    WindSpeed,
+    /// Total cloud cover [0,1]
+    CloudCover,
+    /// Global horizontal irradiance [W/m²]
+    GlobalIrradiance,
+    /// Relative humidity [%]
+    Humidity,
+    /// Air pressure [hPa]
+    Pressure,
+    /// Precipitation [mm/h]
+    Precipitation,
+}
+
+impl WeatherVariable {
+    pub const ALL: [WeatherVariable; 7] = [
+        WeatherVariable::Temperature,
+        WeatherVariable::WindSpeed,
+        WeatherVariable::CloudCover,
+        WeatherVariable::GlobalIrradiance,
+        WeatherVariable::Humidity,
+        WeatherVariable::Pressure,
+        WeatherVariable::Precipitation,
+    ];
+
+    /// Returns the name of the variable as used in weather files
+    ///
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeatherVariable::Temperature => "temperature",
+            WeatherVariable::WindSpeed => "wind_speed",
+            WeatherVariable::CloudCover => "cloud_cover",
+            WeatherVariable::GlobalIrradiance => "global_irradiance",
+            WeatherVariable::Humidity => "humidity",
+            WeatherVariable::Pressure => "pressure",
+            WeatherVariable::Precipitation => "precipitation",
+        }
+    }
+
+    /// Returns the prefix of the variable's cache files, temperature keeps the prefix it had
+    /// before other variables were cached
+    ///
+    pub fn cache_prefix(&self) -> &'static str {
+        match self {
+            WeatherVariable::Temperature => "temp",
+            WeatherVariable::WindSpeed => "wind",
+            WeatherVariable::CloudCover => "cloud",
+            WeatherVariable::GlobalIrradiance => "ghi",
+            WeatherVariable::Humidity => "humidity",
+            WeatherVariable::Pressure => "pressure",
+            WeatherVariable::Precipitation => "precip",
+        }
+    }
+}
+
+/// Weather source, observed weather from a logger, a file or a weather service
+///
+pub trait WeatherSource: Send + Sync {
+    /// Variables the source may have, a station can still lack some of them on a given day
+    fn variables(&self) -> &[WeatherVariable];
+
+    /// Name added to the source's cache files, none for the logger whose cache files predate
+    /// other sources
+    fn cache_id(&self) -> Option<&'static str>;
+
+    /// Number of days after a day is over that the source may still add history for it
+    fn settle_days(&self) -> u64;
+
+    /// Returns samples covering the given period for those of the requested variables the source
+    /// has, samples outside the period are allowed and left out by the caller
+    fn history<'a>(&'a self, variables: &'a [WeatherVariable], from: DateTime<Local>, to: DateTime<Local>) -> SourceFuture<'a>;
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherSourceChoice {
+    /// Home-made logger service, `http://{host}/temperature?id={sensor}&from=&to=`
+    #[default]
+    Logger,
+    /// Local CSV or JSON file
+    File,
+    /// Open-Meteo historical weather archive
+    OpenMeteo,
+    /// SMHI meteorological observations for a station
+    Smhi,
+}
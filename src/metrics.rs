@@ -0,0 +1,117 @@
+use serde::Serialize;
+use crate::models::DataItem;
+
+/// Fit of an estimated power series against actual power
+///
+#[derive(Serialize, Clone, Copy, Default)]
+pub struct FitMetrics {
+    /// Root mean square error [kW]
+    pub rmse: f64,
+    /// Mean absolute error [kW]
+    pub mae: f64,
+    /// Mean bias error, positive when the estimate is too high [kW]
+    pub bias: f64,
+    /// Actual energy over the day [kWh]
+    pub actual_energy: f64,
+    /// Estimated energy over the day [kWh]
+    pub estimated_energy: f64,
+    /// Estimated energy relative to actual energy minus one
+    pub energy_error: f64,
+    /// Number of actual data items compared
+    pub samples: usize,
+}
+
+/// Returns fit metrics for a per minute estimate against actual data
+///
+/// Each actual data item is compared with the estimate for the same minute of the day, actual
+/// items outside the estimated day are ignored.
+///
+/// # Arguments
+///
+/// * 'actual' - actual power data items, not necessarily one per minute
+/// * 'estimated' - estimated power, one data item per minute starting at midnight
+pub fn fit_metrics(actual: &[DataItem], estimated: &[DataItem]) -> FitMetrics {
+    let mut result = FitMetrics {
+        estimated_energy: estimated_energy(estimated),
+        actual_energy: actual_energy(actual),
+        ..Default::default()
+    };
+
+    if estimated.is_empty() {
+        return result;
+    }
+
+    let start = estimated[0].x;
+    let mut n: usize = 0;
+    let (mut sum_sq, mut sum_abs, mut sum) = (0.0, 0.0, 0.0);
+
+    for item in actual {
+        let minute = (item.x - start).num_minutes();
+        if minute < 0 || minute as usize >= estimated.len() {
+            continue;
+        }
+
+        let error = estimated[minute as usize].y - item.y;
+        sum_sq += error * error;
+        sum_abs += error.abs();
+        sum += error;
+        n += 1;
+    }
+
+    result.samples = n;
+    if n > 0 {
+        result.rmse = (sum_sq / n as f64).sqrt();
+        result.mae = sum_abs / n as f64;
+        result.bias = sum / n as f64;
+    }
+    if result.actual_energy > 0.0 {
+        result.energy_error = result.estimated_energy / result.actual_energy - 1.0;
+    }
+
+    result
+}
+
+/// Returns metrics pooled over several days, as if all days were one series
+///
+/// # Arguments
+///
+/// * 'per_day' - metrics for each day
+pub fn pool_metrics(per_day: &[FitMetrics]) -> FitMetrics {
+    let samples = per_day.iter().map(|m| m.samples).sum::<usize>();
+    let weighted = |f: fn(&FitMetrics) -> f64| {
+        per_day.iter().map(|m| f(m) * m.samples as f64).sum::<f64>() / samples.max(1) as f64
+    };
+
+    let actual_energy = per_day.iter().map(|m| m.actual_energy).sum::<f64>();
+    let estimated_energy = per_day.iter().map(|m| m.estimated_energy).sum::<f64>();
+
+    FitMetrics {
+        rmse: weighted(|m| m.rmse * m.rmse).sqrt(),
+        mae: weighted(|m| m.mae),
+        bias: weighted(|m| m.bias),
+        actual_energy,
+        estimated_energy,
+        energy_error: if actual_energy > 0.0 { estimated_energy / actual_energy - 1.0 } else { 0.0 },
+        samples,
+    }
+}
+
+/// Returns energy in kWh from a per minute power series in kW
+///
+/// # Arguments
+///
+/// * 'estimated' - power data items, one per minute
+pub fn estimated_energy(estimated: &[DataItem]) -> f64 {
+    estimated.iter().map(|d| d.y).sum::<f64>() / 60.0
+}
+
+/// Returns energy in kWh from a power series in kW with arbitrary time steps, using the trapezoidal rule
+///
+/// # Arguments
+///
+/// * 'actual' - power data items in time order
+pub fn actual_energy(actual: &[DataItem]) -> f64 {
+    actual.windows(2)
+        .map(|w| (w[0].y + w[1].y) / 2.0 * (w[1].x - w[0].x).num_seconds() as f64 / 3600.0)
+        .sum()
+}
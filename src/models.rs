@@ -9,6 +9,7 @@ pub struct DataItem {
     pub y: f64,
 }
 
+#[derive(Clone)]
 pub struct Parameters {
     pub year: i32,
     pub month: u32,
@@ -16,6 +17,10 @@ pub struct Parameters {
     pub lat: f64,
     pub long: f64,
     pub temp: [f64;1440],
+    pub temp_previous: Option<[f64;1440]>,
+    pub humidity: Option<[f64;1440]>,
+    pub cloud_cover: Option<[f64;1440]>,
+    pub clouds: Option<[f64;1440]>,
     pub panel_power: f64,
     pub panel_slope: f64,
     pub panel_east_azm: f64,
@@ -34,8 +39,35 @@ pub struct Production {
     pub power: Vec<DataItem>,
     pub incidence_east: Vec<DataItem>,
     pub incidence_west: Vec<DataItem>,
+    pub surface_slope: Vec<DataItem>,
     pub ambient_temperature: Vec<DataItem>,
+    pub module_temperature_east: Vec<DataItem>,
+    pub module_temperature_west: Vec<DataItem>,
     pub roof_temperature_east: Vec<DataItem>,
     pub roof_temperature_west: Vec<DataItem>,
+    pub sky_temperature: Vec<DataItem>,
+    pub dew_point: Vec<DataItem>,
+    pub moisture: Vec<Moisture>,
     pub sun_intensity_factor: Vec<DataItem>,
+    pub sun_azimuth: Vec<DataItem>,
+    pub sun_elevation: Vec<DataItem>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MoistureKind {
+    Dew,
+    Frost,
+}
+
+/// A period when a panel plane is colder than the dew point, and thus gets wet or frosted
+///
+#[derive(Serialize)]
+pub struct Moisture {
+    pub plane: &'static str,
+    pub kind: MoistureKind,
+    #[serde(with = "serialize_timestamp")]
+    pub from: DateTime<Local>,
+    #[serde(with = "serialize_timestamp")]
+    pub to: DateTime<Local>,
 }
@@ -0,0 +1,43 @@
+use std::thread;
+
+/// Stack size for worker threads, the production model keeps many per minute arrays on the stack
+const WORKER_STACK_SIZE: usize = 32 * 1024 * 1024;
+
+/// Maps items to results on all available CPU cores
+///
+/// Items are split in contiguous chunks, one per worker thread, and the results are returned
+/// in the same order as the items regardless of which thread finishes first.
+///
+/// # Arguments
+///
+/// * 'items' - items to map
+/// * 'f' - function to apply to each item
+pub fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    if items.is_empty() {
+        return Vec::new();
+    }
+
+    let workers = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = items.len().div_ceil(workers);
+    let f = &f;
+
+    thread::scope(|s| {
+        let handles = items
+            .chunks(chunk_size)
+            .map(|chunk| thread::Builder::new()
+                .stack_size(WORKER_STACK_SIZE)
+                .spawn_scoped(s, move || chunk.iter().map(f).collect::<Vec<R>>())
+                .unwrap())
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
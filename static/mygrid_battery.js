@@ -0,0 +1,104 @@
+// home battery simulation, power flows and state of charge
+//
+let battery_options = {
+    series: [],
+    chart: {
+        id: 'battery',
+        height: 350,
+        type: 'line',
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#FEB019", "#A5978B", "#FF4560", "#00E396", "#775DD0", "#008FFB", "#FFFFFF"],
+    stroke: {
+        curve: 'smooth',
+        width: [2, 2, 1, 1, 1, 1, 2],
+        dashArray: [0, 0, 0, 0, 0, 5, 3],
+    },
+    yaxis: [
+        {
+            seriesName: ['Production', 'Load', 'Import', 'Export', 'Battery', 'Self-consumption'],
+            axisBorder: {
+                show: false
+            },
+            title: {
+                text: 'kW',
+            },
+            labels: {
+                show: true,
+                minWidth: 30,
+                formatter: function (val) {
+                    return Math.round(val * 100) / 100;
+                }
+            }
+        },
+        {
+            seriesName: 'SoC',
+            opposite: true,
+            min: 0,
+            max: 1,
+            axisBorder: {
+                show: false
+            },
+            labels: {
+                show: true,
+                formatter: function (val) {
+                    return Math.round(val * 100) + " %";
+                }
+            }
+        },
+    ],
+    xaxis: {
+        position: 'bottom',
+        type: 'datetime',
+        axisBorder: {
+            show: false,
+        },
+        axisTicks: {
+            show: true,
+        },
+        labels: {
+            show: true,
+        },
+    },
+    tooltip: {
+        enabled: true,
+        shared: true,
+        x: {
+            show: true,
+            format: 'yyyy-MM-dd HH:mm',
+        },
+        y: {
+            formatter: function(value) {
+                return Math.round(value * 100) / 100;
+            }
+        },
+    },
+    title: {
+        text: 'Home Battery',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Battery to simulate...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let battery_chart = new ApexCharts(document.querySelector("#battery"), battery_options);
+battery_chart.render();
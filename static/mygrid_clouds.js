@@ -0,0 +1,89 @@
+// clear-sky index and inferred clouds
+//
+let clouds_options = {
+    series: [],
+    chart: {
+        id: 'clouds',
+        height: 350,
+        type: 'line',
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#008FFB", "#00E396", "#FEB019", "#A5978B"],
+    stroke: {
+        curve: 'smooth',
+        width: [0, 2, 2, 2],
+        dashArray: [0, 0, 0, 5],
+    },
+    markers: {
+        size: [2, 0, 0, 0],
+    },
+    yaxis: {
+        min: 0,
+        max: 1.5,
+        tickAmount: 6,
+        axisBorder: {
+            show: false
+        },
+        labels: {
+            show: true,
+            minWidth: 30,
+            formatter: function (val) {
+                return Math.round(val * 100) / 100;
+            }
+        }
+    },
+    xaxis: {
+        position: 'bottom',
+        type: 'datetime',
+        axisBorder: {
+            show: false,
+        },
+        axisTicks: {
+            show: true,
+        },
+        labels: {
+            show: true,
+        },
+    },
+    tooltip: {
+        enabled: true,
+        shared: true,
+        x: {
+            show: true,
+            format: 'HH:mm',
+        },
+        y: {
+            formatter: function(value) {
+                return Math.round(value * 100) / 100;
+            }
+        },
+    },
+    title: {
+        text: 'Clear-sky Index and Clouds',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Clouds to infer...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let clouds_chart = new ApexCharts(document.querySelector("#clouds"), clouds_options);
+clouds_chart.render();
@@ -0,0 +1,74 @@
+// energy curtailed by the grid export limit per day
+//
+let curtailment_options = {
+    series: [],
+    chart: {
+        id: 'curtailment',
+        height: 350,
+        type: 'bar',
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#00E396", "#FEB019"],
+    plotOptions: {
+        bar: {
+            columnWidth: '60%',
+        },
+    },
+    dataLabels: {
+        enabled: false,
+    },
+    yaxis: {
+        axisBorder: {
+            show: false
+        },
+        labels: {
+            show: true,
+            minWidth: 30,
+            formatter: function (val) {
+                return Math.round(val * 10) / 10 + " kWh";
+            }
+        }
+    },
+    xaxis: {
+        type: 'datetime',
+        labels: {
+            show: true,
+        },
+    },
+    tooltip: {
+        enabled: true,
+        shared: true,
+        x: {
+            show: true,
+            format: 'yyyy-MM-dd',
+        },
+    },
+    title: {
+        text: 'Curtailed Energy per Day',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Curtailment to simulate...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let curtailment_chart = new ApexCharts(document.querySelector("#curtailment"), curtailment_options);
+curtailment_chart.render();
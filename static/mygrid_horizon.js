@@ -0,0 +1,86 @@
+// learned horizon: elevation and transmittance per azimuth bin
+//
+let horizon_options = {
+    series: [],
+    chart: {
+        id: 'horizon',
+        height: 350,
+        type: 'line',
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#00E396", "#FEB019"],
+    stroke: {
+        curve: 'stepline',
+        width: [2, 2],
+        dashArray: [0, 5],
+    },
+    yaxis: [{
+        seriesName: 'Horizon',
+        min: 0,
+        axisBorder: {
+            show: false
+        },
+        labels: {
+            show: true,
+            minWidth: 30,
+            formatter: function (val) {
+                return Math.round(val * 10) / 10 + "°";
+            }
+        }
+    }, {
+        seriesName: 'Transmittance',
+        opposite: true,
+        min: 0,
+        max: 1,
+        labels: {
+            show: true,
+            formatter: function (val) {
+                return Math.round(val * 100) + " %";
+            }
+        }
+    }],
+    xaxis: {
+        type: 'numeric',
+        min: 0,
+        max: 360,
+        tickAmount: 12,
+        labels: {
+            show: true,
+            formatter: function (val) {
+                return Math.round(val) + "°";
+            }
+        },
+    },
+    tooltip: {
+        enabled: true,
+        shared: true,
+    },
+    title: {
+        text: 'Learned Horizon',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Horizon to learn...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let horizon_chart = new ApexCharts(document.querySelector("#horizon"), horizon_options);
+horizon_chart.render();
@@ -14,14 +14,15 @@ let incidence_options = {
             enabled: false,
         },
     },
-    colors: ["#008FFB", "#00E396"],
+    colors: ["#008FFB", "#00E396", "#775DD0"],
     stroke: {
         curve: 'smooth',
-        width: [2,2],
+        width: [2,2,1],
+        dashArray: [0, 0, 4],
     },
     fill: {
         type:'solid',
-        opacity: [1, 1],
+        opacity: [1, 1, 1],
     },
     yaxis: {
         min: 0,
@@ -14,7 +14,7 @@ let prod_options = {
             enabled: false,
         },
     },
-    colors: ["#008FFB", "#00E396"],
+    colors: ["#008FFB", "#00E396", "#FEB019", "#FF4560", "#775DD0"],
     stroke: {
         curve: 'smooth',
         width: [2,2],
@@ -0,0 +1,72 @@
+// tornado chart: change in daily energy when perturbing one parameter at a time
+//
+let sensitivity_options = {
+    series: [],
+    chart: {
+        id: 'sensitivity',
+        height: 450,
+        type: 'bar',
+        stacked: true,
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#FF4560", "#00E396"],
+    plotOptions: {
+        bar: {
+            horizontal: true,
+            barHeight: '70%',
+        },
+    },
+    dataLabels: {
+        enabled: false,
+    },
+    yaxis: {
+        labels: {
+            show: true,
+            minWidth: 100,
+        }
+    },
+    xaxis: {
+        labels: {
+            show: true,
+            formatter: function (val) {
+                return Math.round(val * 100) / 100 + " kWh";
+            }
+        },
+    },
+    tooltip: {
+        enabled: true,
+        y: {
+            formatter: function(value, { series, seriesIndex, dataPointIndex, w }) {
+                return Math.round(value * 100) / 100 + ' kWh';
+            }
+        },
+    },
+    title: {
+        text: 'Energy Sensitivity (one step down/up)',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Sensitivity to analyse...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let sensitivity = new ApexCharts(document.querySelector("#sensitivity"), sensitivity_options);
+sensitivity.render();
@@ -0,0 +1,74 @@
+// forecast skill scores per hour of horizon
+//
+let skill_options = {
+    series: [],
+    chart: {
+        id: 'skill',
+        height: 350,
+        type: 'bar',
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#00E396", "#FEB019"],
+    plotOptions: {
+        bar: {
+            columnWidth: '60%',
+        },
+    },
+    dataLabels: {
+        enabled: false,
+    },
+    yaxis: {
+        axisBorder: {
+            show: false
+        },
+        labels: {
+            show: true,
+            minWidth: 30,
+            formatter: function (val) {
+                return Math.round(val * 100) + " %";
+            }
+        }
+    },
+    xaxis: {
+        type: 'numeric',
+        tickAmount: 'dataPoints',
+        labels: {
+            show: true,
+            formatter: function (val) {
+                return Math.round(val) + " h";
+            }
+        },
+    },
+    tooltip: {
+        enabled: true,
+        shared: true,
+    },
+    title: {
+        text: 'Forecast Skill per Horizon',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Backtest to run...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let skill_chart = new ApexCharts(document.querySelector("#skill"), skill_options);
+skill_chart.render();
@@ -0,0 +1,86 @@
+// production, load and surplus with windows for load shifting
+//
+let surplus_options = {
+    series: [],
+    chart: {
+        id: 'surplus',
+        height: 350,
+        type: 'line',
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#FEB019", "#A5978B", "#00E396"],
+    stroke: {
+        curve: 'smooth',
+        width: [2, 2, 2],
+        dashArray: [0, 5, 0],
+    },
+    annotations: {
+        xaxis: [],
+    },
+    yaxis: {
+        axisBorder: {
+            show: false
+        },
+        labels: {
+            show: true,
+            minWidth: 30,
+            formatter: function (val) {
+                return Math.round(val * 100) / 100;
+            }
+        }
+    },
+    xaxis: {
+        position: 'bottom',
+        type: 'datetime',
+        axisBorder: {
+            show: false,
+        },
+        axisTicks: {
+            show: true,
+        },
+        labels: {
+            show: true,
+        },
+    },
+    tooltip: {
+        enabled: true,
+        shared: true,
+        x: {
+            show: true,
+            format: 'HH:mm',
+        },
+        y: {
+            formatter: function(value) {
+                return Math.round(value * 100) / 100;
+            }
+        },
+    },
+    title: {
+        text: 'PV Surplus',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Surplus to find windows...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let surplus_chart = new ApexCharts(document.querySelector("#surplus"), surplus_options);
+surplus_chart.render();
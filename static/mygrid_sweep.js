@@ -0,0 +1,87 @@
+// parameter sweep: fit error and energy per parameter value
+//
+let sweep_options = {
+    series: [],
+    chart: {
+        id: 'sweep',
+        height: 350,
+        type: 'line',
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#FF4560", "#00E396", "#008FFB"],
+    stroke: {
+        curve: 'straight',
+        width: [2, 2, 2],
+        dashArray: [0, 0, 5],
+    },
+    markers: {
+        size: 3,
+    },
+    yaxis: [{
+        seriesName: 'RMSE',
+        axisBorder: {
+            show: false
+        },
+        labels: {
+            show: true,
+            minWidth: 30,
+            formatter: function (val) {
+                return Math.round(val * 100) / 100 + " kW";
+            }
+        }
+    }, {
+        seriesName: 'Estimated',
+        opposite: true,
+        labels: {
+            show: true,
+            formatter: function (val) {
+                return Math.round(val * 10) / 10 + " kWh";
+            }
+        }
+    }, {
+        seriesName: 'Estimated',
+        show: false,
+    }],
+    xaxis: {
+        type: 'numeric',
+        tickAmount: 10,
+        labels: {
+            show: true,
+            formatter: function (val) {
+                return Math.round(val * 1000) / 1000;
+            }
+        },
+    },
+    tooltip: {
+        enabled: true,
+        shared: true,
+    },
+    title: {
+        text: 'Parameter Sweep',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Sweep to run...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let sweep_chart = new ApexCharts(document.querySelector("#sweep"), sweep_options);
+sweep_chart.render();
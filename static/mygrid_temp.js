@@ -14,10 +14,14 @@ let temp_options = {
             enabled: false,
         },
     },
-    colors: ["#FEB019", "#00E396", "#008FFB"],
+    colors: ["#FEB019", "#00E396", "#008FFB", "#00E396", "#008FFB", "#775DD0", "#FF4560"],
     stroke: {
         curve: 'smooth',
         width: 2,
+        dashArray: [0, 0, 0, 5, 5, 2, 2],
+    },
+    annotations: {
+        xaxis: [],
     },
     fill: {
         type:'solid',
@@ -0,0 +1,74 @@
+// value of production at spot prices per day
+//
+let value_options = {
+    series: [],
+    chart: {
+        id: 'value',
+        height: 350,
+        type: 'bar',
+        toolbar: {
+            show: false,
+        },
+        zoom: {
+            enabled: false,
+        },
+    },
+    colors: ["#00E396", "#008FFB", "#FEB019", "#775DD0", "#FF4560", "#A5978B"],
+    plotOptions: {
+        bar: {
+            columnWidth: '60%',
+        },
+    },
+    dataLabels: {
+        enabled: false,
+    },
+    yaxis: {
+        axisBorder: {
+            show: false
+        },
+        labels: {
+            show: true,
+            minWidth: 30,
+            formatter: function (val) {
+                return Math.round(val * 10) / 10;
+            }
+        }
+    },
+    xaxis: {
+        type: 'datetime',
+        labels: {
+            show: true,
+        },
+    },
+    tooltip: {
+        enabled: true,
+        shared: true,
+        x: {
+            show: true,
+            format: 'yyyy-MM-dd',
+        },
+    },
+    title: {
+        text: 'Production Value per Day',
+        floating: true,
+        offsetY: 0,
+        align: 'center',
+    },
+    noData: {
+        text: 'Press Economics to value...'
+    },
+    theme: {
+        mode: 'dark',
+        palette: 'palette1',
+        monochrome: {
+            enabled: false,
+            color: '#255aee',
+            shadeTo: 'light',
+            shadeIntensity: 0.65
+        },
+    }
+};
+
+
+let value_chart = new ApexCharts(document.querySelector("#value"), value_options);
+value_chart.render();
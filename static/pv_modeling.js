@@ -16,6 +16,319 @@ function loadScriptSequentially(file) {
     });
 }
 
+let lastParams = null;
+
+function headerParams() {
+    let params = {};
+    ["year", "month", "day"].forEach(function(p) {
+        params[p] = parseInt($("#" + p).text());
+    });
+    ["panel_power", "panel_slope", "panel_east_azm", "panel_temp_red", "tau", "tau_down", "k_gain",
+        "iam_factor", "start_azm", "start_elv", "stop_azm", "stop_elv"].forEach(function(p) {
+        params[p] = parseFloat($("#" + p).text());
+    });
+
+    return params;
+}
+
+function compareData() {
+    let params = headerParams();
+    let variants = [{name: "Current"}];
+    if (lastParams !== null) {
+        variants.push({name: "Last update", params: lastParams});
+    }
+
+    $.ajax({
+        url: '/get_comparison',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify({params: params, variants: variants}),
+        dataType: 'json',
+        success: function(resp) {
+            production.updateSeries(resp.prod_diagram);
+            showMetrics(resp.variants);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function nowcastData() {
+    $.ajax({
+        url: '/get_nowcast',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify({params: headerParams()}),
+        dataType: 'json',
+        success: function(resp) {
+            let n = resp.nowcast;
+            production.updateSeries(resp.prod_diagram);
+            $("#metrics").html('<div class="param_container"><h4>Nowcast</h4>' +
+                'ratio ' + n.ratio.toFixed(2) + ', ' + n.energy_so_far.toFixed(1) + ' kWh so far + ' +
+                n.remaining_energy.toFixed(1) + ' kWh remaining = ' + n.expected_energy.toFixed(1) +
+                ' kWh (model ' + n.model_energy.toFixed(1) + ' kWh)</div>');
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function sensitivityData() {
+    $.ajax({
+        url: '/get_sensitivity',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify({params: headerParams()}),
+        dataType: 'json',
+        success: function(resp) {
+            sensitivity.updateSeries(resp.tornado_diagram);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function cloudsData() {
+    $.ajax({
+        url: '/get_clouds',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify({params: headerParams()}),
+        dataType: 'json',
+        success: function(resp) {
+            clouds_chart.updateSeries(resp.cloud_diagram);
+            temp.updateSeries(resp.temp_diagram);
+            showMoisture(resp.moisture);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+let sweepBest = null;
+
+function sweepData() {
+    let request = {
+        params: headerParams(),
+        parameter: $("#sweep_parameter").val(),
+        from: parseFloat($("#sweep_from").text()),
+        to: parseFloat($("#sweep_to").text()),
+        step: parseFloat($("#sweep_step").text()),
+        days: parseInt($("#sweep_days").text()),
+    };
+
+    $.ajax({
+        url: '/get_sweep',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify(request),
+        dataType: 'json',
+        success: function(resp) {
+            sweepBest = {parameter: resp.parameter, value: resp.best};
+            $("#sweep_best").text(resp.best === null ? '-' : resp.best);
+            sweep_chart.updateSeries(resp.sweep_diagram);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function applySweepBest() {
+    if (sweepBest !== null && sweepBest.value !== null) {
+        $("#" + sweepBest.parameter).text(sweepBest.value);
+        updateData();
+    }
+}
+
+function horizonData(save) {
+    let request = {
+        params: headerParams(),
+        days: parseInt($("#horizon_days").text()),
+        bin_width: parseFloat($("#horizon_bin").text()),
+        save: save,
+    };
+
+    $.ajax({
+        url: '/get_horizon',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify(request),
+        dataType: 'json',
+        success: function(resp) {
+            $("#horizon_clear").text(resp.fit.clear_days.length + (resp.saved ? ' (saved)' : ''));
+            horizon_chart.updateSeries(resp.horizon_diagram);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function backtestData() {
+    let request = {
+        params: headerParams(),
+        days: parseInt($("#backtest_days").text()),
+    };
+
+    $.ajax({
+        url: '/get_backtest',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify(request),
+        dataType: 'json',
+        success: function(resp) {
+            $("#backtest_forecasts").text(resp.backtest.forecasts.length);
+            skill_chart.updateSeries(resp.skill_diagram);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function batterySpec() {
+    let minSoc = parseFloat($("#battery_min_soc").text());
+
+    return {
+        capacity: parseFloat($("#battery_capacity").text()),
+        max_charge: parseFloat($("#battery_power").text()),
+        max_discharge: parseFloat($("#battery_power").text()),
+        efficiency: parseFloat($("#battery_efficiency").text()),
+        min_soc: minSoc,
+        max_soc: parseFloat($("#battery_max_soc").text()),
+        initial_soc: minSoc,
+    };
+}
+
+function batteryData() {
+    let request = {
+        params: headerParams(),
+        days: parseInt($("#battery_days").text()),
+        battery: batterySpec(),
+    };
+
+    $.ajax({
+        url: '/get_battery',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify(request),
+        dataType: 'json',
+        success: function(resp) {
+            $("#battery_sufficiency").text(Math.round(resp.total.self_sufficiency * 100) + ' %');
+            battery_chart.updateSeries(resp.power_diagram.concat(resp.soc_diagram));
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function curtailmentData(actual) {
+    let request = {
+        params: headerParams(),
+        days: parseInt($("#curtailment_days").text()),
+        export_limit: parseFloat($("#export_limit").text()),
+        actual: actual,
+    };
+
+    $.ajax({
+        url: '/get_curtailment',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify(request),
+        dataType: 'json',
+        success: function(resp) {
+            let total = function(set) {
+                return set.months.reduce(function(t, m) { return t + m.summary.curtailed; }, 0).toFixed(1);
+            };
+            $("#curtailed_energy").text(total(resp.modelled) + (actual ? ' / ' + total(resp.actual) : '') + ' kWh');
+            curtailment_chart.updateSeries(resp.energy_diagram);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function economicsData(actual) {
+    let request = {
+        params: headerParams(),
+        days: parseInt($("#economics_days").text()),
+        battery: batterySpec(),
+        variants: lastParams !== null ? [{name: "Last update", params: lastParams}] : [],
+        actual: actual,
+    };
+
+    $.ajax({
+        url: '/get_economics',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify(request),
+        dataType: 'json',
+        success: function(resp) {
+            $("#economics_value").text(resp.valuations.map(function(v) {
+                return v.name + ' ' + v.total.value.toFixed(0);
+            }).join(', '));
+            value_chart.updateSeries(resp.value_diagram);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function surplusData(forecast) {
+    let request = {
+        params: headerParams(),
+        threshold: parseFloat($("#surplus_threshold").text()),
+        min_minutes: parseInt($("#surplus_minutes").text()),
+        forecast: forecast,
+    };
+
+    $.ajax({
+        url: '/get_surplus',
+        type: 'POST',
+        contentType: 'application/json',
+        data: JSON.stringify(request),
+        dataType: 'json',
+        success: function(resp) {
+            let time = function(ts) {
+                return new Date(ts).toTimeString().substring(0, 5);
+            };
+            $("#surplus_windows").text(resp.windows.length === 0 ? 'none' : resp.windows.map(function(w) {
+                return time(w.start) + '-' + time(w.end);
+            }).join(', '));
+            surplus_chart.updateOptions({
+                annotations: {
+                    xaxis: resp.windows.map(function(w) {
+                        return {x: w.start, x2: w.end, fillColor: '#00E396', opacity: 0.15};
+                    }),
+                },
+            });
+            surplus_chart.updateSeries(resp.surplus_diagram);
+        },
+        error: function(jqXHR) {
+            displayMessage(jqXHR.responseText, 'error');
+        }
+    });
+}
+
+function showMetrics(variants) {
+    let html = variants.map(function(v) {
+        let m = v.metrics;
+        return '<div class="param_container"><h4>' + v.name + '</h4>' +
+            'RMSE ' + m.rmse.toFixed(3) + ' kW, bias ' + m.bias.toFixed(3) + ' kW, ' +
+            m.estimated_energy.toFixed(1) + ' / ' + m.actual_energy.toFixed(1) + ' kWh (' +
+            (m.energy_error * 100).toFixed(1) + '%)</div>';
+    });
+    $("#metrics").html(html.join(''));
+}
+
 function updateData() {
     let year = $("#year").text();
     let month = $("#month").text();
@@ -43,16 +356,38 @@ function updateData() {
         '&start_azm=' + start_azm + '&start_elv=' + start_elv + '&stop_azm=' + stop_azm + '&stop_elv=' + stop_elv;
 
     $.getJSON(url, function(resp, textStatus, jqXHR) {
+        lastParams = resp.params;
+        $("#metrics").html('');
         production.updateSeries(resp.prod_diagram);
         incidence.updateSeries(resp.incidence_diagram);
         temp.updateSeries(resp.temp_diagram);
+        showMoisture(resp.moisture);
         ame.updateSeries([resp.ame_diagram]);
     });
 }
 
+function showMoisture(moisture) {
+    let annotations = moisture.map(function(m) {
+        return {
+            x: m.from,
+            x2: m.to,
+            fillColor: m.kind === 'frost' ? '#FFFFFF' : '#008FFB',
+            opacity: 0.1,
+            label: {
+                text: m.kind + ' ' + m.plane,
+                orientation: 'horizontal',
+            },
+        };
+    });
+
+    temp.clearAnnotations();
+    annotations.forEach(function(a) { temp.addXaxisAnnotation(a); });
+}
+
 function getData() {
     $.getJSON('/get_start', function(resp, textStatus, jqXHR) {
         console.log(resp.params);
+        lastParams = resp.params;
         $("#year").text(resp.params.year);
         $("#month").text(resp.params.month);
         $("#day").text(resp.params.day);
@@ -76,6 +411,7 @@ function getData() {
         production.updateSeries(resp.prod_diagram);
         incidence.updateSeries(resp.incidence_diagram);
         temp.updateSeries(resp.temp_diagram);
+        showMoisture(resp.moisture);
         ame.updateSeries([resp.ame_diagram]);
 
     });
@@ -86,6 +422,15 @@ loadScriptSequentially('locale_se.js')
     .then(() => loadScriptSequentially('mygrid_incidence.js'))
     .then(() => loadScriptSequentially('mygrid_temp.js'))
     .then(() => loadScriptSequentially('mygrid_ame.js'))
+    .then(() => loadScriptSequentially('mygrid_clouds.js'))
+    .then(() => loadScriptSequentially('mygrid_sweep.js'))
+    .then(() => loadScriptSequentially('mygrid_horizon.js'))
+    .then(() => loadScriptSequentially('mygrid_skill.js'))
+    .then(() => loadScriptSequentially('mygrid_battery.js'))
+    .then(() => loadScriptSequentially('mygrid_curtailment.js'))
+    .then(() => loadScriptSequentially('mygrid_value.js'))
+    .then(() => loadScriptSequentially('mygrid_surplus.js'))
+    .then(() => loadScriptSequentially('mygrid_sensitivity.js'))
     .then(() => {
         getData();
     })